@@ -0,0 +1,88 @@
+use bevy::{
+    prelude::{Assets, Color, Handle, Image},
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+/// The image substituted for a handle whose load failed.
+///
+/// Generated fallbacks are 2D and run through the failed image's [`ImagePipeline`],
+/// so they should be sized to survive it (ex. a height divisible by the layer count of a `Reinterpret`).
+///
+/// [`ImagePipeline`]: super::processor::ImagePipeline
+#[derive(Debug, Clone)]
+pub enum ImageFallback {
+    /// Leave the handle empty; dependents will keep waiting on it.
+    None,
+    /// A magenta / black checkerboard with square cells of `cell` pixels.
+    Checkerboard { width: u32, height: u32, cell: u32 },
+    /// A single pixel of flat color.
+    Flat(Color),
+    /// A copy of an already-loaded image.
+    Image(Handle<Image>),
+}
+
+impl Default for ImageFallback {
+    fn default() -> Self {
+        ImageFallback::Checkerboard {
+            width: 64,
+            height: 64,
+            cell: 8,
+        }
+    }
+}
+
+fn color_to_rgba_u8(color: Color) -> [u8; 4] {
+    color
+        .as_rgba_f32()
+        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+impl ImageFallback {
+    pub fn image(&self, images: &Assets<Image>) -> Option<Image> {
+        match self {
+            ImageFallback::None => None,
+            ImageFallback::Checkerboard {
+                width,
+                height,
+                cell,
+            } => {
+                let on = color_to_rgba_u8(Color::FUCHSIA);
+                let off = color_to_rgba_u8(Color::BLACK);
+                let cell = (*cell).max(1);
+
+                let mut data = Vec::with_capacity((width * height * 4) as usize);
+                for y in 0..*height {
+                    for x in 0..*width {
+                        if ((x / cell) + (y / cell)) % 2 == 0 {
+                            data.extend_from_slice(&on);
+                        } else {
+                            data.extend_from_slice(&off);
+                        }
+                    }
+                }
+
+                Some(Image::new(
+                    Extent3d {
+                        width: *width,
+                        height: *height,
+                        depth_or_array_layers: 1,
+                    },
+                    TextureDimension::D2,
+                    data,
+                    TextureFormat::Rgba8UnormSrgb,
+                ))
+            }
+            ImageFallback::Flat(color) => Some(Image::new_fill(
+                Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &color_to_rgba_u8(*color),
+                TextureFormat::Rgba8UnormSrgb,
+            )),
+            ImageFallback::Image(handle) => images.get(handle).cloned(),
+        }
+    }
+}
@@ -0,0 +1,667 @@
+pub mod fallback;
+pub mod palette_atlas;
+pub mod processor;
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    asset::{AssetLoader, AssetPath, BoxedFuture, HandleId, LoadContext, LoadState},
+    prelude::{
+        debug, error, AddAsset, AssetEvent, AssetServer, Assets, Commands, CoreStage, EventReader,
+        EventWriter, FromWorld, Handle, Image, Plugin, Res, ResMut, Resource, World,
+    },
+    render::texture::{ImageSampler, ImageTextureLoader},
+    utils::HashMap,
+};
+
+use self::{
+    fallback::ImageFallback,
+    palette_atlas::{PaletteAtlas, PaletteAtlasSource},
+    processor::{ImageMeta, ImageMetaLoader, ImagePipeline, SetSampler},
+};
+
+pub struct ImageLoaderPlugin;
+
+impl Plugin for ImageLoaderPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ImageLoader>();
+
+        let queue = app.world.resource::<ImageLoader>().queue();
+        // Replaces bevy's image loader, which must already be registered
+        app.insert_resource(queue)
+            .init_asset_loader::<RecordingImageLoader>()
+            .add_asset::<ImageMeta>()
+            .init_asset_loader::<ImageMetaLoader>()
+            .add_event::<ImageLoadFailed>()
+            .add_system_to_stage(CoreStage::PreUpdate, image_loader);
+    }
+}
+
+/// Sent when an image tracked by [`ImageLoader`] fails to load.
+#[derive(Debug, Clone)]
+pub struct ImageLoadFailed {
+    pub handle: Handle<Image>,
+    pub path: Option<AssetPath<'static>>,
+    pub error: String,
+}
+
+struct ImageEntry {
+    load_state: LoadState,
+    pipeline: ImagePipeline,
+    meta: Option<Handle<ImageMeta>>,
+    fallback: Option<ImageFallback>,
+    error: Option<String>,
+    // Whether the pipeline has run over either the loaded image or its fallback
+    processed: bool,
+    // Set when the image is created or modified, until its meta is available to process it
+    unprocessed: bool,
+    // Modified events caused by our own processing, which must not trigger a rerun
+    pending_modifications: usize,
+}
+
+impl ImageEntry {
+    fn new(pipeline: ImagePipeline, meta: Option<Handle<ImageMeta>>) -> Self {
+        ImageEntry {
+            load_state: LoadState::NotLoaded,
+            pipeline,
+            meta,
+            fallback: None,
+            error: None,
+            processed: false,
+            unprocessed: false,
+            pending_modifications: 0,
+        }
+    }
+}
+
+struct DeferredImage {
+    path: AssetPath<'static>,
+    pipeline: ImagePipeline,
+    fallback: Option<ImageFallback>,
+}
+
+/// A shared queue of image loads that are started by [`image_loader`] on its next run.
+///
+/// Doesn't require the [`AssetServer`], so it can be used during [`Plugin::build`],
+/// and may be cloned into other worlds (ex. the physics sub-app) to load images from there.
+#[derive(Default, Clone, Resource)]
+pub struct ImageLoadQueue(Arc<Mutex<Vec<DeferredImage>>>);
+
+impl ImageLoadQueue {
+    /// Queue an image for loading, returning a weak handle to it.
+    ///
+    /// A strong handle is held by [`ImageLoader`] once the load has started.
+    pub fn load_with_pipeline<P>(&self, path: P, pipeline: ImagePipeline) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>,
+    {
+        self.load_with_fallback(path, pipeline, None)
+    }
+
+    pub fn load_with_fallback<P>(
+        &self,
+        path: P,
+        pipeline: ImagePipeline,
+        fallback: Option<ImageFallback>,
+    ) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>,
+    {
+        let path = path.into();
+        let handle = Handle::weak(path.get_id().into());
+
+        self.0.lock().unwrap().push(DeferredImage {
+            path,
+            pipeline,
+            fallback,
+        });
+
+        handle
+    }
+
+    pub fn load<P: Into<AssetPath<'static>>>(&self, path: P) -> Handle<Image> {
+        self.load_with_pipeline(path, ImagePipeline::default())
+    }
+
+    fn drain(&self) -> Vec<DeferredImage> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+pub trait ImageLoaderCommandsExt {
+    /// Queue an image for loading via the world's [`ImageLoadQueue`], returning a weak handle to it.
+    fn load_image_with_pipeline<P>(&mut self, path: P, pipeline: ImagePipeline) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>;
+
+    fn load_image<P: Into<AssetPath<'static>>>(&mut self, path: P) -> Handle<Image> {
+        self.load_image_with_pipeline(path, ImagePipeline::default())
+    }
+}
+
+impl ImageLoaderCommandsExt for Commands<'_, '_> {
+    fn load_image_with_pipeline<P>(&mut self, path: P, pipeline: ImagePipeline) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>,
+    {
+        let path = path.into();
+        let handle = Handle::weak(path.get_id().into());
+
+        self.add(move |world: &mut World| {
+            world
+                .get_resource::<ImageLoadQueue>()
+                .expect("Missing ImageLoadQueue resource")
+                .load_with_pipeline(path, pipeline);
+        });
+
+        handle
+    }
+}
+
+type LoadErrors = Arc<Mutex<HashMap<PathBuf, String>>>;
+
+/// Wraps bevy's [`ImageTextureLoader`] to keep the error behind each failed load,
+/// since the asset server only logs it.
+struct RecordingImageLoader {
+    loader: ImageTextureLoader,
+    errors: LoadErrors,
+}
+
+impl FromWorld for RecordingImageLoader {
+    fn from_world(world: &mut World) -> Self {
+        RecordingImageLoader {
+            loader: ImageTextureLoader::from_world(world),
+            errors: world.resource::<ImageLoader>().errors.clone(),
+        }
+    }
+}
+
+impl AssetLoader for RecordingImageLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().to_owned();
+            let result = self.loader.load(bytes, load_context).await;
+
+            let mut errors = self.errors.lock().unwrap();
+            match &result {
+                Ok(()) => errors.remove(&path),
+                Err(e) => errors.insert(path, e.to_string()),
+            };
+
+            result
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.loader.extensions()
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct ImageLoader {
+    queue: ImageLoadQueue,
+    errors: LoadErrors,
+    images: HashMap<Handle<Image>, ImageEntry>,
+    atlases: HashMap<Handle<Image>, PaletteAtlas>,
+    default_fallback: ImageFallback,
+}
+
+impl ImageLoader {
+    /// Load an image, running `pipeline` over it each time it is created or hot-reloaded.
+    ///
+    /// Processors described by a `<path>.meta` sidecar are run after `pipeline`.
+    pub fn load_with_pipeline<P>(
+        &mut self,
+        asset_server: &AssetServer,
+        path: P,
+        pipeline: ImagePipeline,
+    ) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>,
+    {
+        let path = path.into();
+        let meta = ImageMeta::load(asset_server, path.path());
+        let handle = asset_server.load::<Image, _>(path);
+        self.images
+            .try_insert(handle.clone(), ImageEntry::new(pipeline, meta))
+            .ok();
+        handle
+    }
+
+    /// Queue an image for loading on the next run of [`image_loader`], returning a weak handle to it.
+    pub fn load_deferred<P>(&self, path: P, pipeline: ImagePipeline) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>,
+    {
+        self.queue.load_with_pipeline(path, pipeline)
+    }
+
+    /// A clone of this loader's deferred load queue.
+    pub fn queue(&self) -> ImageLoadQueue {
+        self.queue.clone()
+    }
+
+    pub fn load_with<P, F>(&mut self, asset_server: &AssetServer, path: P, f: F) -> Handle<Image>
+    where
+        P: Into<AssetPath<'static>>,
+        F: 'static + Send + Sync + FnMut(&mut Image),
+    {
+        self.load_with_pipeline(
+            asset_server,
+            path,
+            ImagePipeline::default().with_fn("load_with", f),
+        )
+    }
+
+    pub fn load<P: Into<AssetPath<'static>>>(
+        &mut self,
+        asset_server: &AssetServer,
+        path: P,
+    ) -> Handle<Image> {
+        self.load_with_pipeline(asset_server, path, ImagePipeline::default())
+    }
+
+    pub fn load_with_sampler<P: Into<AssetPath<'static>>>(
+        &mut self,
+        asset_server: &AssetServer,
+        path: P,
+        sampler: ImageSampler,
+    ) -> Handle<Image> {
+        self.load_with_pipeline(
+            asset_server,
+            path,
+            ImagePipeline::default().with(SetSampler(sampler)),
+        )
+    }
+
+    /// Load a 3D palette texture, which is rebuilt whenever one of its sources is reloaded.
+    pub fn load_palette_atlas(
+        &mut self,
+        asset_server: &AssetServer,
+        images: &Assets<Image>,
+        source: PaletteAtlasSource,
+        sampler: ImageSampler,
+    ) -> Handle<Image> {
+        let sources = match source {
+            PaletteAtlasSource::Directory { directory, prefix } => {
+                let mut paths = match asset_server.asset_io().read_directory(&directory) {
+                    Ok(paths) => paths
+                        .filter(|path| {
+                            path.file_name()
+                                .and_then(|name| name.to_str())
+                                .map(|name| name.starts_with(&prefix) && !name.ends_with(".meta"))
+                                .unwrap_or_default()
+                        })
+                        .collect::<Vec<_>>(),
+                    Err(e) => {
+                        error!("Failed to read palette directory {directory:?}: {e}");
+                        vec![]
+                    }
+                };
+                paths.sort();
+
+                paths
+                    .into_iter()
+                    .map(|path| (self.load(asset_server, path), 1))
+                    .collect()
+            }
+            PaletteAtlasSource::Stacked { path, layers } => {
+                vec![(self.load(asset_server, path), layers)]
+            }
+        };
+
+        self.insert_palette_atlas(images, sources, sampler)
+    }
+
+    fn insert_palette_atlas(
+        &mut self,
+        images: &Assets<Image>,
+        sources: Vec<(Handle<Image>, u32)>,
+        sampler: ImageSampler,
+    ) -> Handle<Image> {
+        let handle = images.get_handle(HandleId::random::<Image>());
+        self.atlases.insert(
+            handle.clone(),
+            PaletteAtlas {
+                sources,
+                sampler,
+                // Sources that were already loaded won't send events to mark it dirty
+                dirty: true,
+                built: false,
+            },
+        );
+        handle
+    }
+
+    /// Set the fallback used for images without one of their own.
+    pub fn set_default_fallback(&mut self, fallback: ImageFallback) {
+        self.default_fallback = fallback;
+    }
+
+    /// Override the fallback substituted if `handle` fails to load.
+    ///
+    /// For palette atlases, the fallback applies to each of their sources.
+    pub fn set_fallback(&mut self, handle: &Handle<Image>, fallback: ImageFallback) {
+        if let Some(atlas) = self.atlases.get(handle) {
+            for (source, _) in atlas.sources.iter() {
+                if let Some(entry) = self.images.get_mut(source) {
+                    entry.fallback = Some(fallback.clone());
+                }
+            }
+        }
+
+        if let Some(entry) = self.images.get_mut(handle) {
+            entry.fallback = Some(fallback);
+        }
+    }
+
+    /// Returns true once the image, or its fallback, is available and processed.
+    pub fn is_loaded(&self, handle: &Handle<Image>) -> bool {
+        if let Some(atlas) = self.atlases.get(handle) {
+            return atlas.built;
+        }
+
+        let Some(entry) = self.images.get(handle) else {
+            return false
+        };
+
+        entry.processed
+    }
+
+    pub fn is_failed(&self, handle: &Handle<Image>) -> bool {
+        let Some(entry) = self.images.get(handle) else {
+            return false
+        };
+
+        entry.load_state == LoadState::Failed
+    }
+
+    /// Iterate over images whose last load failed, alongside their error.
+    pub fn failures(&self) -> impl Iterator<Item = (&Handle<Image>, &str)> {
+        self.images.iter().filter_map(|(handle, entry)| {
+            entry
+                .error
+                .as_deref()
+                .filter(|_| entry.load_state == LoadState::Failed)
+                .map(|error| (handle, error))
+        })
+    }
+}
+
+/// The error behind a failed load, as recorded by [`RecordingImageLoader`].
+///
+/// Loads that fail before reaching the loader are only described by whether the file exists.
+fn load_error(asset_server: &AssetServer, errors: &LoadErrors, path: &AssetPath) -> String {
+    if let Some(error) = errors.lock().unwrap().remove(path.path()) {
+        return error;
+    }
+
+    if asset_server.asset_io().is_file(path.path()) {
+        "Failed to read the file, or no loader supports it".into()
+    } else {
+        "File not found".into()
+    }
+}
+
+pub fn image_loader(
+    mut image_loader: ResMut<ImageLoader>,
+    asset_server: Res<AssetServer>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut meta_events: EventReader<AssetEvent<ImageMeta>>,
+    mut failed_events: EventWriter<ImageLoadFailed>,
+    mut images: ResMut<Assets<Image>>,
+    metas: Res<Assets<ImageMeta>>,
+) {
+    for deferred in image_loader.queue.drain() {
+        debug!("Starting deferred load of {:?}", deferred.path);
+
+        let meta = ImageMeta::load(&asset_server, deferred.path.path());
+        let handle = asset_server.load::<Image, _>(deferred.path);
+        let mut entry = ImageEntry::new(deferred.pipeline, meta);
+        entry.fallback = deferred.fallback;
+        image_loader.images.try_insert(handle, entry).ok();
+    }
+
+    // Failed loads produce no asset events, so poll for them
+    let image_loader = &mut *image_loader;
+    for (handle, entry) in image_loader.images.iter_mut() {
+        if entry.load_state == LoadState::Failed
+            || asset_server.get_load_state(handle) != LoadState::Failed
+        {
+            continue;
+        }
+
+        entry.load_state = LoadState::Failed;
+
+        let path = asset_server
+            .get_handle_path(handle)
+            .map(|path| path.to_owned());
+
+        let error = path
+            .as_ref()
+            .map(|path| load_error(&asset_server, &image_loader.errors, path))
+            .unwrap_or_else(|| "Unknown path".into());
+
+        error!("Failed to load image {path:?}: {error}");
+
+        entry.error = Some(error.clone());
+        failed_events.send(ImageLoadFailed {
+            handle: handle.clone_weak(),
+            path,
+            error,
+        });
+
+        // Substitute the fallback, which will be processed when its Created event arrives
+        if images.get(handle).is_none() {
+            let fallback = entry
+                .fallback
+                .as_ref()
+                .unwrap_or(&image_loader.default_fallback);
+
+            if let Some(image) = fallback.image(&images) {
+                images.set_untracked(handle, image);
+            }
+        }
+    }
+
+    for event in image_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } => handle,
+            AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle } => handle,
+        };
+
+        let Some(entry) = image_loader.images.get_mut(handle) else {
+            continue
+        };
+
+        entry.load_state = asset_server.get_load_state(handle);
+        if entry.load_state == LoadState::Loaded {
+            entry.error = None;
+        }
+
+        match event {
+            AssetEvent::Modified { .. } if entry.pending_modifications > 0 => {
+                entry.pending_modifications -= 1;
+            }
+            AssetEvent::Created { .. } | AssetEvent::Modified { .. } => entry.unprocessed = true,
+            AssetEvent::Removed { .. } => {
+                entry.processed = false;
+                entry.unprocessed = false;
+            }
+        }
+    }
+
+    // Reload images whose meta was edited, so their processors run over a fresh copy
+    for event in meta_events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue
+        };
+
+        for (image, entry) in image_loader.images.iter() {
+            if entry.meta.as_ref() == Some(handle) {
+                if let Some(path) = asset_server.get_handle_path(image) {
+                    debug!("Reloading {path:?} for its modified meta");
+                    asset_server.reload_asset(path);
+                }
+            }
+        }
+    }
+
+    // Process images once their meta, if any, has finished loading
+    for (handle, entry) in image_loader.images.iter_mut() {
+        if !entry.unprocessed {
+            continue;
+        }
+
+        let meta = match &entry.meta {
+            Some(meta) => match asset_server.get_load_state(meta) {
+                LoadState::Loaded => metas.get(meta),
+                LoadState::Failed => None,
+                _ => continue,
+            },
+            None => None,
+        };
+
+        let Some(image) = images.get_mut(handle) else {
+            continue
+        };
+
+        entry.pipeline.process(image);
+
+        if let Some(meta) = meta {
+            debug!("Applying image meta {meta:?}");
+            meta.pipeline().process(image);
+        }
+
+        entry.processed = true;
+        entry.unprocessed = false;
+
+        // Mutable access to the image emits a Modified event of its own
+        entry.pending_modifications += 1;
+
+        for atlas in image_loader.atlases.values_mut() {
+            if atlas.contains(handle) {
+                atlas.dirty = true;
+            }
+        }
+    }
+
+    // Rebuild palette atlases once all of their sources are available
+    for (handle, atlas) in image_loader.atlases.iter_mut() {
+        let ready = atlas.sources.iter().all(|(source, _)| {
+            image_loader
+                .images
+                .get(source)
+                .map(|entry| entry.processed)
+                .unwrap_or_default()
+        });
+
+        if !atlas.dirty || !ready {
+            continue;
+        }
+
+        atlas.dirty = false;
+
+        match atlas.build(&images) {
+            Ok(image) => {
+                images.set_untracked(handle, image);
+                atlas.built = true;
+            }
+            Err(error) => {
+                error!("Failed to build palette atlas: {error}");
+                failed_events.send(ImageLoadFailed {
+                    handle: handle.clone_weak(),
+                    path: None,
+                    error,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::AssetPlugin,
+        prelude::{App, MinimalPlugins, Mut},
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    use super::*;
+
+    fn palette(color: [u8; 4]) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: 4,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &color,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    #[test]
+    fn palette_atlas_builds_from_loaded_sources() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .add_plugin(ImageLoaderPlugin);
+
+        let sources = [[255, 0, 0, 255], [0, 0, 255, 255]].map(|color| {
+            let handle = app
+                .world
+                .resource_mut::<Assets<Image>>()
+                .add(palette(color));
+
+            app.world.resource_mut::<ImageLoader>().images.insert(
+                handle.clone(),
+                ImageEntry::new(ImagePipeline::default(), None),
+            );
+
+            (handle, 1)
+        });
+
+        // Process the sources, then consume the Modified events caused by processing them
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let image_loader = app.world.resource::<ImageLoader>();
+        assert!(sources
+            .iter()
+            .all(|(handle, _)| image_loader.is_loaded(handle)));
+
+        let atlas = app
+            .world
+            .resource_scope(|world, mut image_loader: Mut<ImageLoader>| {
+                image_loader.insert_palette_atlas(
+                    world.resource::<Assets<Image>>(),
+                    sources.to_vec(),
+                    ImageSampler::Default,
+                )
+            });
+
+        app.update();
+
+        assert!(app.world.resource::<ImageLoader>().is_loaded(&atlas));
+
+        let image = app
+            .world
+            .resource::<Assets<Image>>()
+            .get(&atlas)
+            .expect("Palette atlas wasn't built");
+        assert_eq!(image.texture_descriptor.dimension, TextureDimension::D3);
+        assert_eq!(image.texture_descriptor.size.depth_or_array_layers, 2);
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use bevy::{
+    asset::AssetPath,
+    prelude::{Assets, Handle, Image},
+    render::{
+        render_resource::{Extent3d, TextureDimension},
+        texture::{ImageSampler, TextureFormatPixelInfo},
+    },
+};
+
+/// Where the layers of a 3D palette texture come from.
+#[derive(Debug, Clone)]
+pub enum PaletteAtlasSource {
+    /// Every file in `directory` whose name starts with `prefix`, one layer each, in name order.
+    Directory { directory: PathBuf, prefix: String },
+    /// A single image containing `layers` vertically stacked palettes.
+    Stacked {
+        path: AssetPath<'static>,
+        layers: u32,
+    },
+}
+
+/// A 3D palette texture assembled from one or more loaded images.
+pub(super) struct PaletteAtlas {
+    // Each source image, alongside the number of palettes stacked within it
+    pub sources: Vec<(Handle<Image>, u32)>,
+    pub sampler: ImageSampler,
+    pub dirty: bool,
+    pub built: bool,
+}
+
+impl PaletteAtlas {
+    pub fn contains(&self, handle: &Handle<Image>) -> bool {
+        self.sources.iter().any(|(source, _)| source == handle)
+    }
+
+    /// Stack the source images into a 3D texture, validating that every layer shares
+    /// the same dimensions and format.
+    pub fn build(&self, images: &Assets<Image>) -> Result<Image, String> {
+        let mut layers = vec![];
+
+        for (handle, count) in self.sources.iter() {
+            let image = images
+                .get(handle)
+                .ok_or_else(|| format!("Missing palette source {handle:?}"))?;
+
+            let descriptor = &image.texture_descriptor;
+            if descriptor.dimension != TextureDimension::D2
+                || descriptor.size.depth_or_array_layers != 1
+            {
+                return Err(format!(
+                    "Palette source {handle:?} is not a single-layer 2D image"
+                ));
+            }
+
+            let Extent3d { width, height, .. } = descriptor.size;
+            if *count == 0 || height % count != 0 {
+                return Err(format!(
+                    "Palette source {handle:?} of height {height} can't be split into {count} layers"
+                ));
+            }
+
+            let height = height / count;
+            let len = (width * height) as usize * descriptor.format.pixel_size();
+            for i in 0..*count as usize {
+                layers.push((
+                    width,
+                    height,
+                    descriptor.format,
+                    &image.data[i * len..(i + 1) * len],
+                ));
+            }
+        }
+
+        let Some((width, height, format, _)) = layers.first().copied() else {
+            return Err("Palette atlas has no layers".into())
+        };
+
+        let mut data = vec![];
+        for (i, (layer_width, layer_height, layer_format, layer_data)) in
+            layers.iter().enumerate()
+        {
+            if (*layer_width, *layer_height, *layer_format) != (width, height, format) {
+                return Err(format!(
+                    "Palette layer {i} is {layer_width}x{layer_height} {layer_format:?}, expected {width}x{height} {format:?}"
+                ));
+            }
+
+            data.extend_from_slice(layer_data);
+        }
+
+        let mut image = Image::new(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: layers.len() as u32,
+            },
+            TextureDimension::D3,
+            data,
+            format,
+        );
+        image.sampler_descriptor = self.sampler.clone();
+
+        Ok(image)
+    }
+}
@@ -0,0 +1,409 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{AssetLoader, AssetServer, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::{debug, default, warn, Handle, Image},
+    reflect::TypeUuid,
+    render::{
+        render_resource::{
+            AddressMode, Extent3d, FilterMode, SamplerDescriptor, TextureDimension, TextureFormat,
+        },
+        texture::ImageSampler,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// A named transformation applied to an [`Image`] each time it is loaded or hot-reloaded.
+pub trait ImageProcessor: 'static + Send + Sync {
+    fn name(&self) -> Cow<'static, str>;
+    fn process(&mut self, image: &mut Image);
+}
+
+/// Adapts a closure into an [`ImageProcessor`].
+pub struct ImageProcessorFn<F> {
+    name: Cow<'static, str>,
+    f: F,
+}
+
+impl<F> ImageProcessorFn<F>
+where
+    F: 'static + Send + Sync + FnMut(&mut Image),
+{
+    pub fn new(name: impl Into<Cow<'static, str>>, f: F) -> Self {
+        ImageProcessorFn {
+            name: name.into(),
+            f,
+        }
+    }
+}
+
+impl<F> ImageProcessor for ImageProcessorFn<F>
+where
+    F: 'static + Send + Sync + FnMut(&mut Image),
+{
+    fn name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+
+    fn process(&mut self, image: &mut Image) {
+        (self.f)(image)
+    }
+}
+
+/// An ordered chain of [`ImageProcessor`]s.
+#[derive(Default)]
+pub struct ImagePipeline {
+    processors: Vec<Box<dyn ImageProcessor>>,
+}
+
+impl ImagePipeline {
+    pub fn with<P: ImageProcessor>(mut self, processor: P) -> Self {
+        self.push(processor);
+        self
+    }
+
+    pub fn with_fn<F>(self, name: impl Into<Cow<'static, str>>, f: F) -> Self
+    where
+        F: 'static + Send + Sync + FnMut(&mut Image),
+    {
+        self.with(ImageProcessorFn::new(name, f))
+    }
+
+    pub fn push<P: ImageProcessor>(&mut self, processor: P) -> &mut Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    pub fn push_boxed(&mut self, processor: Box<dyn ImageProcessor>) -> &mut Self {
+        self.processors.push(processor);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = Cow<'static, str>> + '_ {
+        self.processors.iter().map(|processor| processor.name())
+    }
+
+    pub fn process(&mut self, image: &mut Image) {
+        for processor in self.processors.iter_mut() {
+            debug!("Running image processor {}", processor.name());
+            processor.process(image);
+        }
+    }
+}
+
+/// Reinterprets a vertically stacked 2D image as `layers` slices of the given dimension.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Reinterpret {
+    pub layers: u32,
+    pub dimension: TextureDimension,
+}
+
+impl ImageProcessor for Reinterpret {
+    fn name(&self) -> Cow<'static, str> {
+        "reinterpret".into()
+    }
+
+    fn process(&mut self, image: &mut Image) {
+        let size = image.texture_descriptor.size;
+        if self.layers == 0 || size.height % self.layers != 0 {
+            warn!(
+                "Can't reinterpret image of height {} as {} layers",
+                size.height, self.layers
+            );
+            return;
+        }
+
+        image.reinterpret_size(Extent3d {
+            width: size.width,
+            height: size.height / self.layers,
+            depth_or_array_layers: self.layers,
+        });
+        image.texture_descriptor.dimension = self.dimension;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetSampler(pub ImageSampler);
+
+impl ImageProcessor for SetSampler {
+    fn name(&self) -> Cow<'static, str> {
+        "set_sampler".into()
+    }
+
+    fn process(&mut self, image: &mut Image) {
+        image.sampler_descriptor = self.0.clone();
+    }
+}
+
+/// Converts the image to another format.
+/// Only the formats supported by [`Image::convert`] are valid targets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConvertFormat(pub TextureFormat);
+
+impl ImageProcessor for ConvertFormat {
+    fn name(&self) -> Cow<'static, str> {
+        "convert_format".into()
+    }
+
+    fn process(&mut self, image: &mut Image) {
+        let Some(converted) = image.convert(self.0) else {
+            warn!(
+                "Can't convert image from {:?} to {:?}",
+                image.texture_descriptor.format, self.0
+            );
+            return
+        };
+
+        *image = Image {
+            sampler_descriptor: image.sampler_descriptor.clone(),
+            ..converted
+        };
+    }
+}
+
+/// Generates a box-filtered mip chain for single-layer 2D images with 8-bit RGBA / BGRA formats.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct GenerateMips;
+
+impl ImageProcessor for GenerateMips {
+    fn name(&self) -> Cow<'static, str> {
+        "generate_mips".into()
+    }
+
+    fn process(&mut self, image: &mut Image) {
+        let descriptor = &image.texture_descriptor;
+
+        if descriptor.dimension != TextureDimension::D2
+            || descriptor.size.depth_or_array_layers != 1
+            || descriptor.mip_level_count != 1
+        {
+            warn!("Mip generation is only supported for single-layer 2D images without mips");
+            return;
+        }
+
+        match descriptor.format {
+            TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb => (),
+            format => {
+                warn!("Mip generation is not supported for {format:?}");
+                return;
+            }
+        }
+
+        let mut width = descriptor.size.width as usize;
+        let mut height = descriptor.size.height as usize;
+        let mut level = image.data.clone();
+        let mut mip_level_count = 1;
+
+        while width > 1 || height > 1 {
+            let next_width = (width / 2).max(1);
+            let next_height = (height / 2).max(1);
+            let mut next = vec![0u8; next_width * next_height * 4];
+
+            for y in 0..next_height {
+                for x in 0..next_width {
+                    for c in 0..4 {
+                        let mut sum = 0u32;
+                        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                            let sx = (x * 2 + dx).min(width - 1);
+                            let sy = (y * 2 + dy).min(height - 1);
+                            sum += level[(sy * width + sx) * 4 + c] as u32;
+                        }
+                        next[(y * next_width + x) * 4 + c] = (sum / 4) as u8;
+                    }
+                }
+            }
+
+            image.data.extend_from_slice(&next);
+            level = next;
+            width = next_width;
+            height = next_height;
+            mip_level_count += 1;
+        }
+
+        image.texture_descriptor.mip_level_count = mip_level_count;
+    }
+}
+
+/// Serializable mirror of [`TextureDimension`] for use in `.meta` files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetaDimension {
+    D1,
+    D2,
+    D3,
+}
+
+impl From<MetaDimension> for TextureDimension {
+    fn from(value: MetaDimension) -> Self {
+        match value {
+            MetaDimension::D1 => TextureDimension::D1,
+            MetaDimension::D2 => TextureDimension::D2,
+            MetaDimension::D3 => TextureDimension::D3,
+        }
+    }
+}
+
+/// Serializable mirror of [`AddressMode`] for use in `.meta` files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetaAddressMode {
+    ClampToEdge,
+    Repeat,
+    MirrorRepeat,
+}
+
+impl From<MetaAddressMode> for AddressMode {
+    fn from(value: MetaAddressMode) -> Self {
+        match value {
+            MetaAddressMode::ClampToEdge => AddressMode::ClampToEdge,
+            MetaAddressMode::Repeat => AddressMode::Repeat,
+            MetaAddressMode::MirrorRepeat => AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Serializable mirror of [`FilterMode`] for use in `.meta` files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetaFilterMode {
+    Nearest,
+    Linear,
+}
+
+impl From<MetaFilterMode> for FilterMode {
+    fn from(value: MetaFilterMode) -> Self {
+        match value {
+            MetaFilterMode::Nearest => FilterMode::Nearest,
+            MetaFilterMode::Linear => FilterMode::Linear,
+        }
+    }
+}
+
+/// The subset of [`TextureFormat`] supported by [`ConvertFormat`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetaFormat {
+    R8Unorm,
+    Rg8Unorm,
+    Rgba8UnormSrgb,
+}
+
+impl From<MetaFormat> for TextureFormat {
+    fn from(value: MetaFormat) -> Self {
+        match value {
+            MetaFormat::R8Unorm => TextureFormat::R8Unorm,
+            MetaFormat::Rg8Unorm => TextureFormat::Rg8Unorm,
+            MetaFormat::Rgba8UnormSrgb => TextureFormat::Rgba8UnormSrgb,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MetaProcessor {
+    Reinterpret {
+        layers: u32,
+        dimension: MetaDimension,
+    },
+    Sampler {
+        address_mode: MetaAddressMode,
+        filter: MetaFilterMode,
+    },
+    ConvertFormat(MetaFormat),
+    GenerateMips,
+}
+
+impl From<MetaProcessor> for Box<dyn ImageProcessor> {
+    fn from(value: MetaProcessor) -> Self {
+        match value {
+            MetaProcessor::Reinterpret { layers, dimension } => Box::new(Reinterpret {
+                layers,
+                dimension: dimension.into(),
+            }),
+            MetaProcessor::Sampler {
+                address_mode,
+                filter,
+            } => Box::new(SetSampler(ImageSampler::Descriptor(SamplerDescriptor {
+                address_mode_u: address_mode.into(),
+                address_mode_v: address_mode.into(),
+                address_mode_w: address_mode.into(),
+                mag_filter: filter.into(),
+                min_filter: filter.into(),
+                mipmap_filter: filter.into(),
+                ..default()
+            }))),
+            MetaProcessor::ConvertFormat(format) => Box::new(ConvertFormat(format.into())),
+            MetaProcessor::GenerateMips => Box::new(GenerateMips),
+        }
+    }
+}
+
+/// RON sidecar describing the processors to run on an image,
+/// loaded as an asset from `<image path>.meta` so edits to it are hot-reloaded.
+///
+/// ```ron
+/// (
+///     processors: [
+///         Reinterpret(layers: 6, dimension: D3),
+///         Sampler(address_mode: ClampToEdge, filter: Nearest),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TypeUuid)]
+#[uuid = "1836dd60-28fe-4047-bb5b-af7698c4a108"]
+pub struct ImageMeta {
+    #[serde(default)]
+    pub processors: Vec<MetaProcessor>,
+}
+
+impl ImageMeta {
+    pub fn path(image_path: &Path) -> PathBuf {
+        let mut path = image_path.as_os_str().to_owned();
+        path.push(".meta");
+        path.into()
+    }
+
+    /// Starts loading the sidecar for `image_path`, if one exists.
+    pub fn load(asset_server: &AssetServer, image_path: &Path) -> Option<Handle<ImageMeta>> {
+        let path = ImageMeta::path(image_path);
+        asset_server
+            .asset_io()
+            .is_file(&path)
+            .then(|| asset_server.load(path))
+    }
+
+    pub fn pipeline(&self) -> ImagePipeline {
+        let mut pipeline = ImagePipeline::default();
+        for processor in self.processors.iter().cloned() {
+            pipeline.push_boxed(processor.into());
+        }
+        pipeline
+    }
+}
+
+/// Parses `.meta` sidecars into [`ImageMeta`] assets.
+#[derive(Default)]
+pub struct ImageMetaLoader;
+
+impl AssetLoader for ImageMetaLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let meta = ron::de::from_bytes::<ImageMeta>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(meta));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["meta"]
+    }
+}
@@ -50,20 +50,19 @@ use bevy::{
     prelude::*,
     render::{
         mesh::Indices,
-        render_resource::{
-            AddressMode, Extent3d, FilterMode, PrimitiveTopology, SamplerDescriptor,
-            TextureDimension,
-        },
+        render_resource::{AddressMode, FilterMode, PrimitiveTopology, SamplerDescriptor},
         texture::ImageSampler,
     },
     window::PresentMode,
 };
 use bevy_inspector_egui::quick::AssetInspectorPlugin;
-use bevy_rapier3d::{
-    prelude::{Collider, RapierConfiguration, RigidBody, Sensor, TimestepMode, Vect},
-    render::RapierDebugRenderPlugin,
+use bevy_rapier3d::prelude::{
+    Collider, RapierConfiguration, RigidBody, Sensor, TimestepMode, Vect,
+};
+use image_loader::{
+    fallback::ImageFallback, palette_atlas::PaletteAtlasSource, ImageLoadQueue, ImageLoader,
+    ImageLoaderPlugin,
 };
-use image_loader::{ImageLoader, ImageLoaderPlugin};
 use internal_assets::InternalAssetsPlugin;
 use material_loader::{MaterialLoader, MaterialLoaderPlugin};
 use npbr::{
@@ -76,8 +75,8 @@ use npbr::{
     NpbrPlugin,
 };
 use physics::{
-    extract_component::ExtractComponentPlugin, extract_param::Extract, LerpTransform, PhysicsApp,
-    PhysicsAppBuilder, PhysicsPlugin, PhysicsStage,
+    component::PhysicsComponentAppExt, debug::PhysicsDebugPlugin, world::DefaultPhysicsWorld,
+    LerpTransform, PhysicsApp, PhysicsAppBuilder, PhysicsPlugin, PhysicsStage,
 };
 use std::{
     borrow::Cow,
@@ -219,32 +218,21 @@ fn main() {
         ..default()
     })
     .add_plugin(PhysicsPlugin::<()>::default())
-    .add_plugin(RapierDebugRenderPlugin::default())
-    .add_startup_system(|mut physics_app: ResMut<PhysicsApp>| {
-        physics_app.schedule.add_system_to_stage(
-            PhysicsStage::Extract,
-            |mut commands: Commands, query: Extract<Query<(Entity, &TimelineComponent)>>| {
-                for (entity, timeline) in query.iter() {
-                    let mut commands = commands.get_or_spawn(entity);
-                    let mut timeline = *timeline;
-
-                    // FIXME: These need to be multiplied by the current tick rate
-                    //        Will likely need a fixed-tick resource to exist inside PhysicsApp::world
-                    //        Better yet, use RapierConfiguration
-                    timeline.timestamp = timeline.timestamp.floor();
-                    timeline.prev_timestamp = timeline.prev_timestamp.floor();
-                    commands.insert(timeline);
-                }
-            },
-        );
-    })
-    .add_plugin(ExtractComponentPlugin::<Torus>::default());
+    .add_plugin(PhysicsDebugPlugin::<DefaultPhysicsWorld>::default())
+    .register_physics_component::<Torus>();
 
     app.add_plugin(ImageLoaderPlugin)
         .add_plugin(MaterialLoaderPlugin::<PaletteLightingMaterial>::default())
         .add_plugin(NpbrPlugin)
         .add_plugin(PaletteLightingPlugin);
 
+    // Allow physics-side systems to queue image loads
+    let image_load_queue = app.world.resource::<ImageLoadQueue>().clone();
+    app.world
+        .resource_mut::<PhysicsApp>()
+        .world
+        .insert_resource(image_load_queue);
+
     app.add_startup_system(setup_scene);
 
     app.add_system(key_input);
@@ -264,6 +252,8 @@ struct Cube;
 #[derive(Debug, Default, Copy, Clone, Component)]
 pub struct Torus;
 
+physics_component!(Torus: extract(()));
+
 #[derive(Debug, Default, Copy, Clone, Component)]
 pub struct Quad;
 
@@ -503,6 +493,7 @@ const ALPHA_SDF_2D: AlphaFunction = AlphaFunction(Cow::Borrowed(
 fn setup_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
     mut shaders: ResMut<Assets<Shader>>,
     materials: Res<Assets<PaletteLightingMaterial>>,
     mut animations: ResMut<AnimationSchedule>,
@@ -512,15 +503,15 @@ fn setup_scene(
     type_registry: Res<AppTypeRegistry>,
     asset_server: Res<AssetServer>,
 ) {
-    let palette_combined =
-        image_loader.load_with(&asset_server, "assets/palette/Combined.png", |image| {
-            image.reinterpret_size(Extent3d {
-                width: image.texture_descriptor.size.width,
-                height: image.texture_descriptor.size.height / 6,
-                depth_or_array_layers: 6,
-            });
-            image.texture_descriptor.dimension = TextureDimension::D3;
-        });
+    let palette_combined = image_loader.load_palette_atlas(
+        &asset_server,
+        &images,
+        PaletteAtlasSource::Stacked {
+            path: "assets/palette/Combined.png".into(),
+            layers: 6,
+        },
+        ImageSampler::Default,
+    );
 
     let noise_bayer = image_loader.load_with_sampler(
         &asset_server,
@@ -534,6 +525,17 @@ fn setup_scene(
         ImageSampler::Descriptor(SAMPLER_DITHER),
     );
 
+    image_loader.set_fallback(
+        &palette_combined,
+        ImageFallback::Checkerboard {
+            width: 16,
+            height: 96,
+            cell: 4,
+        },
+    );
+    image_loader.set_fallback(&noise_bayer, ImageFallback::Flat(Color::GRAY));
+    image_loader.set_fallback(&noise_blue, ImageFallback::Flat(Color::GRAY));
+
     let mut physics_animations = physics_app.world.resource_mut::<AnimationSchedule>();
 
     physics_animations.add(
@@ -1128,11 +1130,35 @@ mod material_loader {
 
     use bevy::{
         asset::{Asset, HandleId},
-        prelude::{default, info, Assets, Handle, Plugin, Res, ResMut, Resource},
+        prelude::{default, info, Assets, Handle, Image, Plugin, Res, ResMut, Resource, Shader},
         utils::HashMap,
     };
 
-    use crate::{image_loader::ImageLoader, npbr::palette_lighting::PaletteLightingMaterial};
+    use crate::image_loader::ImageLoader;
+
+    /// A material whose image and shader dependencies must be loaded before it can be used.
+    pub trait MaterialDependencies {
+        /// Images loaded through the [`ImageLoader`].
+        fn image_dependencies(&self) -> Vec<&Handle<Image>>;
+
+        fn shader_dependencies(&self) -> Vec<&Handle<Shader>> {
+            vec![]
+        }
+
+        fn dependencies_loaded(
+            &self,
+            image_loader: &ImageLoader,
+            shaders: &Assets<Shader>,
+        ) -> bool {
+            self.image_dependencies()
+                .into_iter()
+                .all(|handle| image_loader.is_loaded(handle))
+                && self
+                    .shader_dependencies()
+                    .into_iter()
+                    .all(|handle| shaders.contains(handle))
+        }
+    }
 
     #[derive(Debug, Copy, Clone, Default)]
     pub struct MaterialLoaderPlugin<T> {
@@ -1141,11 +1167,11 @@ mod material_loader {
 
     impl<T> Plugin for MaterialLoaderPlugin<T>
     where
-        T: Asset,
+        T: Asset + MaterialDependencies,
     {
         fn build(&self, app: &mut bevy::prelude::App) {
             app.init_resource::<MaterialLoader<T>>()
-                .add_system(material_loader);
+                .add_system(material_loader::<T>);
         }
     }
 
@@ -1168,13 +1194,12 @@ mod material_loader {
         }
     }
 
-    impl MaterialLoader<PaletteLightingMaterial> {
-        pub fn load(
-            &mut self,
-            materials: &Assets<PaletteLightingMaterial>,
-            material: PaletteLightingMaterial,
-        ) -> Handle<PaletteLightingMaterial> {
-            let handle_id = HandleId::random::<PaletteLightingMaterial>();
+    impl<T> MaterialLoader<T>
+    where
+        T: Asset + MaterialDependencies,
+    {
+        pub fn load(&mut self, materials: &Assets<T>, material: T) -> Handle<T> {
+            let handle_id = HandleId::random::<T>();
             let mut handle = Handle::weak(handle_id);
             self.materials.insert(handle.clone(), material);
             handle.make_strong(&materials);
@@ -1182,15 +1207,18 @@ mod material_loader {
         }
     }
 
-    pub fn material_loader(
-        mut material_loader: ResMut<MaterialLoader<PaletteLightingMaterial>>,
-        mut materials: ResMut<Assets<PaletteLightingMaterial>>,
+    pub fn material_loader<T>(
+        mut material_loader: ResMut<MaterialLoader<T>>,
+        mut materials: ResMut<Assets<T>>,
         image_loader: Res<ImageLoader>,
-    ) {
-        for (mut handle, material) in material_loader.materials.drain_filter(|_, material| {
-            image_loader.is_loaded(&material.palette_texture)
-                && image_loader.is_loaded(&material.dither_texture)
-        }) {
+        shaders: Res<Assets<Shader>>,
+    ) where
+        T: Asset + MaterialDependencies,
+    {
+        for (mut handle, material) in material_loader
+            .materials
+            .drain_filter(|_, material| material.dependencies_loaded(&image_loader, &shaders))
+        {
             info!("Loaded material with handle {handle:?}\n{material:#?}");
             handle.make_strong(&materials);
             materials.set_untracked(handle, material);
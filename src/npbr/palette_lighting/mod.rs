@@ -17,7 +17,10 @@ use bevy::{
     },
 };
 
-use crate::{image_loader::ImageLoader, load_internal_asset, npbr::BaseMaterialUniform};
+use crate::{
+    image_loader::ImageLoader, load_internal_asset, material_loader::MaterialDependencies,
+    npbr::BaseMaterialUniform,
+};
 
 use super::{
     dither::DitherInput,
@@ -335,6 +338,16 @@ impl Material for PaletteLightingMaterial {
     }
 }
 
+impl MaterialDependencies for PaletteLightingMaterial {
+    fn image_dependencies(&self) -> Vec<&Handle<Image>> {
+        vec![&self.palette_texture, &self.dither_texture]
+    }
+
+    fn shader_dependencies(&self) -> Vec<&Handle<Shader>> {
+        self.shader_handle.iter().collect()
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PaletteLightingMaterialKey {
     cull_mode: Option<Face>,
@@ -373,11 +386,10 @@ pub fn palette_lighting_material(
     mut commands: Commands,
     query: Query<(Entity, &PaletteLightingMaterial)>,
     image_loader: Res<ImageLoader>,
+    shaders: Res<Assets<Shader>>,
 ) {
     for (entity, material) in query.iter() {
-        if image_loader.is_loaded(&material.palette_texture)
-            && image_loader.is_loaded(&material.dither_texture)
-        {
+        if material.dependencies_loaded(&image_loader, &shaders) {
             commands.add(move |world: &mut World| {
                 let material = world
                     .entity_mut(entity)
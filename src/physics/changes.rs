@@ -0,0 +1,77 @@
+use bevy::{
+    ecs::component::ComponentTicks,
+    prelude::{Entity, Resource, World},
+    utils::HashSet,
+};
+
+/// Physics-world record of when the main and physics worlds were last synced,
+/// so extraction and writeback only copy components that changed in the meantime.
+///
+/// Components changed by a writeback aren't extracted again,
+/// and components changed by an extraction aren't written back again,
+/// so bodies Rapier leaves alone (ex. sleeping ones) cost nothing after their first extraction.
+///
+/// Change ticks are enough to tell those bodies apart, as Rapier's writeback only assigns
+/// a body's `Transform`, `Velocity` and `Sleeping` when their value differs from the simulation's,
+/// leaving their ticks untouched while it's asleep.
+#[derive(Debug, Default, Resource)]
+pub struct PhysicsChanges {
+    /// Main-world tick as of the last extraction.
+    main_extracted: u32,
+    /// Main-world ticks bracketing the last writeback.
+    main_written_back: (u32, u32),
+    /// Physics-world tick as of the last extraction.
+    physics_extracted: u32,
+    /// Entities with at least one component copied by the last extraction.
+    pub extracted: HashSet<Entity>,
+}
+
+impl PhysicsChanges {
+    /// Whether a main-world component has changed since the last extraction,
+    /// other than by being written back.
+    pub fn changed_in_main(&self, ticks: &ComponentTicks, main_tick: u32) -> bool {
+        let (written_back_start, written_back_end) = self.main_written_back;
+        let written_back = ticks.is_changed(written_back_start, main_tick)
+            && !ticks.is_changed(written_back_end, main_tick);
+
+        ticks.is_changed(self.main_extracted, main_tick) && !written_back
+    }
+
+    /// Whether a physics-world component has changed since the last extraction.
+    pub fn changed_in_physics(&self, ticks: &ComponentTicks, physics_tick: u32) -> bool {
+        ticks.is_changed(self.physics_extracted, physics_tick)
+    }
+
+    pub(super) fn begin_extract(physics_world: &mut World) {
+        physics_world
+            .resource_mut::<PhysicsChanges>()
+            .extracted
+            .clear();
+    }
+
+    /// Close the extraction window once its buffers have been applied.
+    pub(super) fn end_extract(main_world: &mut World, physics_world: &mut World) {
+        // Incremented so that later changes land on a strictly newer tick
+        let main_extracted = main_world.increment_change_tick();
+        let physics_extracted = physics_world.increment_change_tick();
+
+        let mut changes = physics_world.resource_mut::<PhysicsChanges>();
+        changes.main_extracted = main_extracted;
+        changes.physics_extracted = physics_extracted;
+    }
+
+    /// Apply `f`'s writeback to the main world, recording the ticks it changed components at.
+    pub(super) fn writeback(
+        main_world: &mut World,
+        physics_world: &mut World,
+        f: impl FnOnce(&mut World),
+    ) {
+        let start = main_world.increment_change_tick();
+        f(main_world);
+        let end = main_world.increment_change_tick();
+
+        physics_world
+            .resource_mut::<PhysicsChanges>()
+            .main_written_back = (start, end);
+    }
+}
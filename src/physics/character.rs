@@ -0,0 +1,108 @@
+use bevy::prelude::{Commands, Component, Entity, Query, World};
+use bevy_rapier3d::prelude::{KinematicCharacterController, Vect};
+
+use super::world::PhysicsWorld;
+
+/// Main-world sum of the translations requested of a [`KinematicCharacterController`]
+/// since the last physics task was dispatched.
+///
+/// Requests are moved here every frame, so those made while a task is in flight
+/// add up instead of overwriting each other.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct QueuedTranslation(pub Vect);
+
+/// Physics-world share of the queued translations that's still to be applied,
+/// spread evenly over the ticks of the task it was handed over to.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct PendingTranslation {
+    pub remaining: Vect,
+    pub per_tick: Vect,
+}
+
+/// Move translations requested of the character controllers of `W` into their [`QueuedTranslation`],
+/// clearing the request in the main world.
+pub fn queue_translations<W: PhysicsWorld>(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut KinematicCharacterController,
+            Option<&mut QueuedTranslation>,
+        ),
+        W::Membership,
+    >,
+) {
+    for (entity, mut controller, queued) in query.iter_mut() {
+        let Some(translation) = controller.translation else {
+            continue
+        };
+
+        controller.translation = None;
+
+        match queued {
+            Some(mut queued) => queued.0 += translation,
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(QueuedTranslation(translation));
+            }
+        }
+    }
+}
+
+/// Hand the queued translations of `W`'s controllers over to its physics world,
+/// adding to what's left of earlier ones.
+///
+/// Controllers that haven't been extracted yet keep theirs queued.
+pub fn pend_translations<W: PhysicsWorld>(main_world: &mut World, physics_world: &mut World) {
+    let mut query = main_world.query_filtered::<(Entity, &mut QueuedTranslation), W::Membership>();
+    for (entity, mut queued) in query.iter_mut(main_world) {
+        if queued.0 == Vect::ZERO {
+            continue;
+        }
+
+        let Some(mut physics_entity) = physics_world.get_entity_mut(entity) else {
+            continue
+        };
+
+        let mut pending = physics_entity
+            .get::<PendingTranslation>()
+            .copied()
+            .unwrap_or_default();
+
+        pending.remaining += std::mem::take(&mut queued.0);
+
+        physics_entity.insert(pending);
+    }
+}
+
+/// Spread what's left of each controller's [`PendingTranslation`] over the next `ticks` ticks.
+pub fn spread_pending_translations(physics_world: &mut World, ticks: usize) {
+    let mut query = physics_world.query::<&mut PendingTranslation>();
+    for mut pending in query.iter_mut(physics_world) {
+        pending.per_tick = pending.remaining / ticks.max(1) as f32;
+    }
+}
+
+/// Request this tick's share of each controller's [`PendingTranslation`],
+/// on top of any translation requested by other physics-side systems.
+pub fn apply_pending_translations(
+    mut query: Query<(&mut KinematicCharacterController, &mut PendingTranslation)>,
+) {
+    for (mut controller, mut pending) in query.iter_mut() {
+        if pending.remaining == Vect::ZERO {
+            continue;
+        }
+
+        // The last share takes whatever rounding left over
+        let translation = if pending.per_tick.length_squared() >= pending.remaining.length_squared()
+        {
+            pending.remaining
+        } else {
+            pending.per_tick
+        };
+
+        pending.remaining -= translation;
+        controller.translation = Some(controller.translation.unwrap_or_default() + translation);
+    }
+}
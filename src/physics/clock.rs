@@ -0,0 +1,82 @@
+use bevy::prelude::{Query, Res, Resource, World};
+use bevy_rapier3d::prelude::{RapierConfiguration, TimestepMode};
+
+use crate::timeline::TimelineComponent;
+
+/// Physics-world clock mapping between timeline timestamps and simulation ticks,
+/// derived from the world's [`RapierConfiguration`].
+#[derive(Debug, Copy, Clone, PartialEq, Resource)]
+pub struct PhysicsClock {
+    /// The tick currently being simulated, or last simulated outside of a step.
+    pub tick: usize,
+    /// Seconds of simulation covered by a single tick.
+    pub dt: f64,
+    /// Rapier substeps taken per tick.
+    pub substeps: usize,
+}
+
+impl Default for PhysicsClock {
+    fn default() -> Self {
+        PhysicsClock::from_config(&RapierConfiguration::default())
+    }
+}
+
+impl PhysicsClock {
+    pub fn from_config(config: &RapierConfiguration) -> Self {
+        let (dt, substeps) = match config.timestep_mode {
+            TimestepMode::Fixed { dt, substeps } => (dt, substeps),
+            TimestepMode::Variable {
+                max_dt, substeps, ..
+            } => (max_dt, substeps),
+            TimestepMode::Interpolated { dt, substeps, .. } => (dt, substeps),
+        };
+
+        PhysicsClock {
+            tick: 0,
+            dt: dt as f64,
+            substeps,
+        }
+    }
+
+    /// Refresh the tick rate from the world's [`RapierConfiguration`], if it has one.
+    pub fn sync(world: &mut World) {
+        let Some(config) = world.get_resource::<RapierConfiguration>() else {
+            return
+        };
+
+        let PhysicsClock { dt, substeps, .. } = PhysicsClock::from_config(config);
+
+        let mut clock = world.get_resource_or_insert_with(PhysicsClock::default);
+        clock.dt = dt;
+        clock.substeps = substeps;
+    }
+
+    /// The last tick starting at or before `timestamp`.
+    pub fn tick_at(&self, timestamp: f64) -> usize {
+        (timestamp.max(0.0) / self.dt).floor() as usize
+    }
+
+    /// Timeline timestamp at which `tick` starts.
+    pub fn timestamp_at(&self, tick: usize) -> f64 {
+        tick as f64 * self.dt
+    }
+
+    /// Timeline timestamp of the current tick.
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp_at(self.tick)
+    }
+
+    /// Round `timestamp` down to the start of its tick.
+    pub fn snap(&self, timestamp: f64) -> f64 {
+        self.timestamp_at(self.tick_at(timestamp))
+    }
+}
+
+/// Moves physics-world timelines to the tick being simulated,
+/// so systems running per tick see the time they're simulating rather than the task's target.
+pub fn tick_timeline(clock: Res<PhysicsClock>, mut query: Query<&mut TimelineComponent>) {
+    for mut timeline in query.iter_mut() {
+        timeline.prev_timestamp = clock.timestamp_at(clock.tick.saturating_sub(1));
+        timeline.timestamp = clock.timestamp();
+    }
+}
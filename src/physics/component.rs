@@ -0,0 +1,222 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::query::ReadOnlyWorldQuery,
+    prelude::{default, App, Component, Plugin, With},
+};
+
+use crate::animation::AnimationStorage;
+
+use super::{
+    extract_component::ExtractComponentPlugin,
+    world::{DefaultPhysicsWorld, PhysicsWorld},
+    writeback_component::WritebackComponentPlugin,
+};
+
+/// A component that crosses the boundary between the main and physics worlds.
+///
+/// Usually implemented via [`physics_component!`](crate::physics_component),
+/// and registered with [`PhysicsComponentAppExt::register_physics_component`].
+pub trait PhysicsComponent: Clone + Component {
+    /// Filter for entities whose component is copied into the physics world.
+    type ExtractFilter: 'static + Send + Sync + ReadOnlyWorldQuery;
+    /// Filter for entities whose component is copied back into the main world.
+    type WritebackFilter: 'static + Send + Sync + ReadOnlyWorldQuery;
+
+    const EXTRACT: bool;
+    const WRITEBACK: bool;
+}
+
+/// Implements [`PhysicsComponent`] for a type.
+///
+/// ```ignore
+/// physics_component!(Velocity: extract(With<RigidBody>), writeback(With<RigidBody>));
+/// physics_component!(Collider: extract(()));
+/// physics_component!(TransformInterpolation: writeback(With<RigidBody>));
+/// ```
+#[macro_export]
+macro_rules! physics_component {
+    ($ty:ty: extract($extract:ty), writeback($writeback:ty)) => {
+        impl $crate::physics::component::PhysicsComponent for $ty {
+            type ExtractFilter = $extract;
+            type WritebackFilter = $writeback;
+
+            const EXTRACT: bool = true;
+            const WRITEBACK: bool = true;
+        }
+    };
+    ($ty:ty: extract($extract:ty)) => {
+        impl $crate::physics::component::PhysicsComponent for $ty {
+            type ExtractFilter = $extract;
+            type WritebackFilter = ();
+
+            const EXTRACT: bool = true;
+            const WRITEBACK: bool = false;
+        }
+    };
+    ($ty:ty: writeback($writeback:ty)) => {
+        impl $crate::physics::component::PhysicsComponent for $ty {
+            type ExtractFilter = ();
+            type WritebackFilter = $writeback;
+
+            const EXTRACT: bool = false;
+            const WRITEBACK: bool = true;
+        }
+    };
+}
+
+/// Marks an entity whose state is driven by systems in the physics app,
+/// such as kinematic animations or game logic computed at a fixed tick.
+///
+/// Its [`Transform`](bevy::prelude::Transform), [`LerpTransform`](super::LerpTransform)
+/// and registered [`AnimationStorage`] values are extracted when a task starts
+/// and written back when it's joined, overwriting any changes made in the main world meanwhile.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct PhysicsDriven;
+
+/// Written back from entities marked [`PhysicsDriven`] once registered,
+/// ex. `app.register_physics_component::<AnimationStorage<f32>>()`.
+impl<T> PhysicsComponent for AnimationStorage<T>
+where
+    T: 'static + Send + Sync + Clone,
+{
+    type ExtractFilter = With<PhysicsDriven>;
+    type WritebackFilter = With<PhysicsDriven>;
+
+    const EXTRACT: bool = true;
+    const WRITEBACK: bool = true;
+}
+
+/// Adds the extract and / or writeback systems declared by a [`PhysicsComponent`]
+/// to the physics world `W`.
+#[derive(Debug)]
+pub struct PhysicsComponentPlugin<T, W = DefaultPhysicsWorld> {
+    phantom: PhantomData<(T, W)>,
+}
+
+impl<T, W> Default for PhysicsComponentPlugin<T, W> {
+    fn default() -> Self {
+        Self { phantom: default() }
+    }
+}
+
+impl<T, W> Plugin for PhysicsComponentPlugin<T, W>
+where
+    T: PhysicsComponent,
+    W: PhysicsWorld,
+{
+    fn build(&self, app: &mut App) {
+        if T::EXTRACT {
+            app.add_plugin(ExtractComponentPlugin::<T, T::ExtractFilter, W>::default());
+        }
+
+        if T::WRITEBACK {
+            app.add_plugin(WritebackComponentPlugin::<T, T::WritebackFilter, W>::default());
+        }
+    }
+}
+
+pub trait PhysicsComponentAppExt {
+    /// Register `T` with the [`DefaultPhysicsWorld`].
+    fn register_physics_component<T: PhysicsComponent>(&mut self) -> &mut Self;
+
+    /// Register `T` with the physics world `W`.
+    fn register_physics_component_in<T: PhysicsComponent, W: PhysicsWorld>(&mut self) -> &mut Self;
+}
+
+impl PhysicsComponentAppExt for App {
+    fn register_physics_component<T: PhysicsComponent>(&mut self) -> &mut Self {
+        self.register_physics_component_in::<T, DefaultPhysicsWorld>()
+    }
+
+    fn register_physics_component_in<T: PhysicsComponent, W: PhysicsWorld>(&mut self) -> &mut Self {
+        self.add_plugin(PhysicsComponentPlugin::<T, W>::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        diagnostic::DiagnosticsPlugin,
+        prelude::{default, App, HierarchyPlugin, MinimalPlugins, Query, TransformPlugin, With},
+    };
+    use bevy_rapier3d::prelude::RapierConfiguration;
+
+    use crate::{
+        animation::AnimationStorage,
+        physics::{
+            clock::PhysicsClock, PhysicsApp, PhysicsAppBuilder, PhysicsPlugin, PhysicsStage,
+        },
+        timeline::{Timeline, TimelineComponent},
+    };
+
+    use super::{PhysicsComponentAppExt, PhysicsDriven};
+
+    /// Count the ticks simulated in the storage of each physics-driven entity.
+    fn count_ticks(mut query: Query<&mut AnimationStorage<f32>, With<PhysicsDriven>>) {
+        for mut storage in query.iter_mut() {
+            storage.value += 1.0;
+        }
+    }
+
+    #[test]
+    fn physics_driven_storage_is_written_back() {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(DiagnosticsPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin);
+
+        app.insert_resource(
+            PhysicsAppBuilder::<()>::default()
+                .map(|app| {
+                    app.add_system_to_stage(PhysicsStage::PostPhysics, count_ticks);
+                })
+                .build(),
+        )
+        .add_plugin(PhysicsPlugin::<()>::default())
+        .register_physics_component::<AnimationStorage<f32>>();
+
+        let driven = app
+            .world
+            .spawn((PhysicsDriven, AnimationStorage::<f32>::default()))
+            .id();
+        let undriven = app.world.spawn(AnimationStorage::<f32>::default()).id();
+
+        // Park the timeline mid-way through tick 10
+        let clock = PhysicsClock::from_config(app.world.resource::<RapierConfiguration>());
+        app.world.spawn(TimelineComponent(Timeline {
+            timestamp: clock.timestamp_at(10) + clock.dt * 0.5,
+            ..default()
+        }));
+
+        for _ in 0..1000 {
+            app.update();
+
+            // The physics app is only present in the main world between tasks
+            let joined = app
+                .world
+                .get_resource::<PhysicsApp>()
+                .map(|physics_app| physics_app.current_tick() >= 10)
+                .unwrap_or_default();
+
+            if joined {
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let value = |entity| {
+            app.world
+                .get::<AnimationStorage<f32>>(entity)
+                .unwrap()
+                .value
+        };
+        assert_eq!(value(driven), 11.0);
+        assert_eq!(value(undriven), 0.0);
+    }
+}
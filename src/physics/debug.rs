@@ -0,0 +1,473 @@
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::{
+    prelude::{
+        Camera, Changed, Commands, Component, CoreStage, Entity, GlobalTransform, Local, Parent,
+        Plugin, Query, Res, ResMut, Resource, SystemStage, Vec2, Vec3, With, Without,
+    },
+    utils::HashMap,
+};
+use bevy_egui::{
+    egui::{self, plot::Line, Color32, Painter, Stroke},
+    EguiContext,
+};
+use bevy_rapier3d::{
+    prelude::{RapierContext, RapierRigidBodyHandle, RigidBody, Velocity},
+    rapier::{
+        geometry::Collider,
+        math::{Point, Real},
+        pipeline::{DebugRenderBackend, DebugRenderObject, DebugRenderPipeline},
+        prelude::RigidBodySet,
+    },
+};
+
+use crate::physics_component;
+
+use super::{
+    clock::PhysicsClock,
+    component::PhysicsComponentAppExt,
+    query::PhysicsQuery,
+    world::{DefaultPhysicsWorld, PhysicsWorld},
+    LerpTransform, PhysicsApp, PhysicsStage,
+};
+
+/// What [`PhysicsDebugPlugin`] draws over the scene.
+#[derive(Debug, Copy, Clone, Resource)]
+pub struct PhysicsDebugSettings {
+    pub enabled: bool,
+    /// Collider outlines, colored by whether their body is asleep.
+    pub colliders: bool,
+    /// Active contact points and their normals.
+    pub contacts: bool,
+    /// [`LerpTransform`] samples, newest brightest.
+    pub lerp_history: bool,
+    /// Ticks of [`VelocityHistory`] kept per body.
+    pub velocity_ticks: usize,
+}
+
+impl Default for PhysicsDebugSettings {
+    fn default() -> Self {
+        PhysicsDebugSettings {
+            enabled: true,
+            colliders: true,
+            contacts: true,
+            lerp_history: true,
+            velocity_ticks: 120,
+        }
+    }
+}
+
+/// Velocity of a body at each of the last few simulated ticks, oldest first.
+///
+/// Filled from the [`VelocitySamples`] written back by each physics task.
+/// Inserted on every body while [`PhysicsDebugSettings::enabled`] is set.
+#[derive(Debug, Default, Clone, Component)]
+pub struct VelocityHistory {
+    pub capacity: usize,
+    pub samples: VecDeque<(usize, Velocity)>,
+}
+
+impl VelocityHistory {
+    pub fn new(capacity: usize) -> Self {
+        VelocityHistory {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, tick: usize, velocity: Velocity) {
+        // Stepping backwards invalidates any samples from the future
+        while self
+            .samples
+            .back()
+            .map(|(back, _)| *back >= tick)
+            .unwrap_or_default()
+        {
+            self.samples.pop_back();
+        }
+
+        self.samples.push_back((tick, velocity));
+        while self.samples.len() > self.capacity.max(1) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Velocity of a body at each tick simulated since its samples were last moved
+/// into its [`VelocityHistory`], oldest first.
+///
+/// Recorded in the physics world from Rapier's bodies, so bodies that are asleep
+/// or moving at a constant velocity are sampled every tick too.
+/// Emptied in the main world once written back, which clears it for the next task.
+#[derive(Debug, Default, Clone, Component)]
+pub struct VelocitySamples(pub Vec<(usize, Velocity)>);
+
+physics_component!(VelocitySamples: extract(With<RigidBody>), writeback(With<RigidBody>));
+
+/// Debug overlay and body inspector for the physics world `W`,
+/// drawn from its last completed state rather than whatever the main world holds mid-task.
+pub struct PhysicsDebugPlugin<W = DefaultPhysicsWorld> {
+    phantom: PhantomData<W>,
+}
+
+impl<W> Default for PhysicsDebugPlugin<W> {
+    fn default() -> Self {
+        PhysicsDebugPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<W: PhysicsWorld> Plugin for PhysicsDebugPlugin<W> {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<PhysicsDebugSettings>()
+            .register_physics_component_in::<VelocitySamples, W>();
+
+        app.add_startup_system(|mut physics_app: ResMut<PhysicsApp<W>>| {
+            physics_app
+                .schedule
+                .get_stage_mut::<SystemStage>(PhysicsStage::PostPhysics)
+                .unwrap()
+                .add_system(sample_velocities);
+        });
+
+        app.add_system(track_velocity_history)
+            .add_system_to_stage(CoreStage::PostUpdate, record_velocity_history::<W>)
+            .add_system(debug_overlay::<W>)
+            .add_system(body_panel::<W>);
+    }
+}
+
+/// Record the velocity each body with [`VelocitySamples`] ends the tick on.
+fn sample_velocities(
+    clock: Res<PhysicsClock>,
+    context: Res<RapierContext>,
+    mut query: Query<(&RapierRigidBodyHandle, &mut VelocitySamples)>,
+) {
+    let physics_scale = context.physics_scale();
+
+    for (handle, mut samples) in query.iter_mut() {
+        let Some(body) = context.bodies.get(handle.0) else {
+            continue
+        };
+
+        let linvel = body.linvel() * physics_scale;
+        let angvel = body.angvel();
+        samples.0.push((
+            clock.tick,
+            Velocity {
+                linvel: Vec3::new(linvel.x, linvel.y, linvel.z),
+                angvel: Vec3::new(angvel.x, angvel.y, angvel.z),
+            },
+        ));
+    }
+}
+
+/// Move the samples written back by the last joined task into each body's history.
+fn record_velocity_history<W: PhysicsWorld>(
+    mut query: Query<
+        (&mut VelocitySamples, &mut VelocityHistory),
+        (Changed<VelocitySamples>, W::Membership),
+    >,
+) {
+    for (mut samples, mut history) in query.iter_mut() {
+        // Emptying them counts as a change, so only do so once
+        if samples.0.is_empty() {
+            continue;
+        }
+
+        for (tick, velocity) in std::mem::take(&mut samples.0) {
+            history.push(tick, velocity);
+        }
+    }
+}
+
+fn track_velocity_history(
+    mut commands: Commands,
+    settings: Res<PhysicsDebugSettings>,
+    query_untracked: Query<Entity, (With<RigidBody>, Without<VelocityHistory>)>,
+    query_tracked: Query<Entity, With<VelocityHistory>>,
+) {
+    if settings.enabled {
+        for entity in query_untracked.iter() {
+            commands.entity(entity).insert((
+                VelocityHistory::new(settings.velocity_ticks),
+                VelocitySamples::default(),
+            ));
+        }
+    } else {
+        for entity in query_tracked.iter() {
+            commands
+                .entity(entity)
+                .remove::<(VelocityHistory, VelocitySamples)>();
+        }
+    }
+}
+
+/// Projects world-space points onto the egui layer covering the active camera.
+struct Projection<'a> {
+    camera: &'a Camera,
+    camera_transform: &'a GlobalTransform,
+    height: f32,
+}
+
+impl Projection<'_> {
+    fn project(&self, point: Vec3) -> Option<egui::Pos2> {
+        // Viewport coordinates start at the bottom left, egui's at the top left
+        let Vec2 { x, y } = self
+            .camera
+            .world_to_viewport(self.camera_transform, point)?;
+        Some(egui::pos2(x, self.height - y))
+    }
+
+    fn line(&self, painter: &Painter, from: Vec3, to: Vec3, stroke: Stroke) {
+        if let (Some(from), Some(to)) = (self.project(from), self.project(to)) {
+            painter.line_segment([from, to], stroke);
+        }
+    }
+
+    fn point(&self, painter: &Painter, point: Vec3, radius: f32, color: Color32) {
+        if let Some(point) = self.project(point) {
+            painter.circle_filled(point, radius, color);
+        }
+    }
+}
+
+fn collider_color(collider: &Collider, bodies: &RigidBodySet) -> Color32 {
+    let sleeping = collider
+        .parent()
+        .and_then(|handle| bodies.get(handle))
+        .map(|body| body.is_sleeping())
+        .unwrap_or_default();
+
+    if collider.is_sensor() {
+        Color32::YELLOW
+    } else if sleeping {
+        Color32::DARK_GRAY
+    } else {
+        Color32::GREEN
+    }
+}
+
+/// Draws the collider outlines produced by Rapier's [`DebugRenderPipeline`] onto the egui layer.
+struct OutlineBackend<'a> {
+    projection: &'a Projection<'a>,
+    painter: &'a Painter,
+    bodies: &'a RigidBodySet,
+    physics_scale: f32,
+}
+
+impl DebugRenderBackend for OutlineBackend<'_> {
+    fn draw_line(
+        &mut self,
+        object: DebugRenderObject,
+        a: Point<Real>,
+        b: Point<Real>,
+        _color: [f32; 4],
+    ) {
+        let DebugRenderObject::Collider(_, collider) = object else {
+            return
+        };
+
+        let to_vec3 =
+            |point: Point<Real>| Vec3::new(point.x, point.y, point.z) * self.physics_scale;
+        let stroke = Stroke::new(1.0, collider_color(collider, self.bodies));
+        self.projection
+            .line(self.painter, to_vec3(a), to_vec3(b), stroke);
+    }
+}
+
+fn debug_overlay<W: PhysicsWorld>(
+    mut ctx: ResMut<EguiContext>,
+    mut outlines: Local<DebugRenderPipeline>,
+    settings: Res<PhysicsDebugSettings>,
+    mut physics_query: ResMut<PhysicsQuery<W>>,
+    query_camera: Query<(&Camera, &GlobalTransform)>,
+    query_lerp_transform: Query<(&LerpTransform, Option<&Parent>)>,
+    query_parent: Query<&GlobalTransform>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    // Only what's drawn is copied for queries
+    if settings.contacts {
+        physics_query.request_contacts();
+    } else if settings.colliders {
+        physics_query.request_scene();
+    }
+
+    let Some((camera, camera_transform)) = query_camera.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return
+    };
+
+    let Some(size) = camera.logical_viewport_size() else {
+        return
+    };
+
+    let projection = Projection {
+        camera,
+        camera_transform,
+        height: size.y,
+    };
+
+    let painter = ctx.ctx_mut().layer_painter(egui::LayerId::background());
+    let physics_scale = physics_query.physics_scale();
+    let to_vec3 = |x: f32, y: f32, z: f32| Vec3::new(x, y, z) * physics_scale;
+
+    if settings.colliders {
+        let mut backend = OutlineBackend {
+            projection: &projection,
+            painter: &painter,
+            bodies: &physics_query.bodies,
+            physics_scale,
+        };
+
+        outlines.render_colliders(
+            &mut backend,
+            &physics_query.bodies,
+            &physics_query.colliders,
+        );
+    }
+
+    if settings.contacts {
+        for pair in physics_query.narrow_phase.contact_pairs() {
+            if !pair.has_any_active_contact {
+                continue;
+            }
+
+            for manifold in pair.manifolds.iter() {
+                let normal = manifold.data.normal;
+                let normal = Vec3::new(normal.x, normal.y, normal.z) * 0.25;
+
+                for contact in manifold.data.solver_contacts.iter() {
+                    let point = to_vec3(contact.point.x, contact.point.y, contact.point.z);
+                    projection.point(&painter, point, 3.0, Color32::RED);
+                    projection.line(
+                        &painter,
+                        point,
+                        point + normal,
+                        Stroke::new(1.0, Color32::RED),
+                    );
+                }
+            }
+        }
+    }
+
+    if settings.lerp_history {
+        for (lerp_transform, parent) in query_lerp_transform.iter() {
+            // Samples are local, like the transforms they were taken from
+            let parent_transform = parent
+                .and_then(|parent| query_parent.get(parent.get()).ok())
+                .copied()
+                .unwrap_or_default();
+
+            let points: Vec<Vec3> = lerp_transform
+                .samples
+                .iter()
+                .map(|sample| parent_transform.transform_point(sample.transform.translation))
+                .collect();
+
+            for (i, point) in points.iter().enumerate() {
+                let alpha = 255 - (i * 192 / points.len().max(1)) as u8;
+                let color = Color32::from_rgba_unmultiplied(0, 160, 255, alpha);
+
+                projection.point(&painter, *point, 2.5, color);
+                if let Some(next) = points.get(i + 1) {
+                    projection.line(&painter, *point, *next, Stroke::new(1.0, color));
+                }
+            }
+        }
+    }
+}
+
+fn body_panel<W: PhysicsWorld>(
+    mut ctx: ResMut<EguiContext>,
+    mut settings: ResMut<PhysicsDebugSettings>,
+    mut physics_query: ResMut<PhysicsQuery<W>>,
+    query_history: Query<&VelocityHistory>,
+) {
+    let title = format!("Physics Bodies ({:?})", W::default());
+
+    let response = egui::Window::new(title)
+        .default_open(false)
+        .show(ctx.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.enabled, "Overlay");
+                ui.checkbox(&mut settings.colliders, "Colliders");
+                ui.checkbox(&mut settings.contacts, "Contacts");
+                ui.checkbox(&mut settings.lerp_history, "Lerp history");
+            });
+
+            ui.label(format!("State as of tick {}", physics_query.tick()));
+            ui.separator();
+
+            // Sorted so the list doesn't reshuffle as bodies are added
+            let mut bodies: Vec<_> = physics_query
+                .bodies
+                .iter()
+                .map(|(_, body)| (Entity::from_bits(body.user_data as u64), body))
+                .collect();
+            bodies.sort_by_key(|(entity, _)| *entity);
+
+            let histories: HashMap<Entity, &VelocityHistory> = bodies
+                .iter()
+                .filter_map(|(entity, _)| Some((*entity, query_history.get(*entity).ok()?)))
+                .collect();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (entity, body) in bodies {
+                    let sleeping = if body.is_sleeping() { ", asleep" } else { "" };
+                    let header = format!("{entity:?} ({:?}{sleeping})", body.body_type());
+
+                    egui::CollapsingHeader::new(header)
+                        .id_source(entity)
+                        .show(ui, |ui| {
+                            let Some(history) = histories.get(&entity) else {
+                                ui.label("No velocity history yet");
+                                return
+                            };
+
+                            velocity_history(ui, entity, history);
+                        });
+                }
+            });
+        });
+
+    // Bodies are only copied for queries while they're listed
+    if response.and_then(|response| response.inner).is_some() {
+        physics_query.request_scene();
+    }
+}
+
+fn velocity_history(ui: &mut egui::Ui, entity: Entity, history: &VelocityHistory) {
+    let speeds: Vec<[f64; 2]> = history
+        .samples
+        .iter()
+        .map(|(tick, velocity)| [*tick as f64, velocity.linvel.length() as f64])
+        .collect();
+
+    egui::widgets::plot::Plot::new(("velocity_history", entity))
+        .height(80.0)
+        .include_y(0)
+        .show(ui, |plot| plot.line(Line::new(speeds)));
+
+    egui::Grid::new(("velocity_grid", entity))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Tick");
+            ui.label("Linear");
+            ui.label("Angular");
+            ui.end_row();
+
+            for (tick, velocity) in history.samples.iter().rev() {
+                let Velocity { linvel, angvel } = velocity;
+
+                ui.label(tick.to_string());
+                ui.label(format!("{:.2} {:.2} {:.2}", linvel.x, linvel.y, linvel.z));
+                ui.label(format!("{:.2} {:.2} {:.2}", angvel.x, angvel.y, angvel.z));
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use bevy::{
+    diagnostic::DiagnosticsPlugin,
+    prelude::{
+        default, App, Entity, HierarchyPlugin, MinimalPlugins, Query, Res, ResMut, Resource,
+        Transform, TransformBundle, TransformPlugin, Vec3, With,
+    },
+};
+use bevy_rapier3d::prelude::{
+    Collider, RapierConfiguration, RigidBody, TimestepMode, Vect, Velocity,
+};
+
+use crate::timeline::{Timeline, TimelineComponent};
+
+use super::{
+    clock::PhysicsClock, progress::PhysicsBudget, PhysicsApp, PhysicsAppBuilder, PhysicsPlugin,
+    PhysicsStage,
+};
+
+/// Per-tick hashes of every rigid body's transform and velocity, keyed by tick then entity.
+pub type TickHashes = BTreeMap<usize, BTreeMap<Entity, u64>>;
+
+/// Physics-world record of [`TickHashes`], filled in as ticks are simulated.
+/// Re-simulated ticks overwrite their previous hashes.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct DeterminismLog {
+    pub ticks: TickHashes,
+}
+
+pub fn record_determinism(
+    clock: Res<PhysicsClock>,
+    mut log: ResMut<DeterminismLog>,
+    query: Query<(Entity, &Transform, Option<&Velocity>), With<RigidBody>>,
+) {
+    let hashes = query
+        .iter()
+        .map(|(entity, transform, velocity)| (entity, hash_body(transform, velocity)))
+        .collect();
+
+    log.ticks.insert(clock.tick, hashes);
+}
+
+fn hash_body(transform: &Transform, velocity: Option<&Velocity>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    let mut floats = vec![];
+    floats.extend(transform.translation.to_array());
+    floats.extend(transform.rotation.to_array());
+    floats.extend(transform.scale.to_array());
+
+    if let Some(velocity) = velocity {
+        floats.extend(velocity.linvel.to_array());
+        floats.extend(velocity.angvel.to_array());
+    }
+
+    // Bitwise, as determinism means identical results rather than approximately equal ones
+    for float in floats {
+        float.to_bits().hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+/// The first point at which two runs of the same scene disagree.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: usize,
+    /// The first entity whose state differs,
+    /// or `None` if one run didn't simulate the tick at all.
+    pub entity: Option<Entity>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.entity {
+            Some(entity) => write!(f, "tick {} at entity {:?}", self.tick, entity),
+            None => write!(f, "tick {}, which only one run simulated", self.tick),
+        }
+    }
+}
+
+/// Find the first tick, and the first entity within it, at which `lhs` and `rhs` differ.
+pub fn first_divergence(lhs: &TickHashes, rhs: &TickHashes) -> Option<Divergence> {
+    let ticks: BTreeSet<usize> = lhs.keys().chain(rhs.keys()).copied().collect();
+
+    ticks.into_iter().find_map(|tick| {
+        let (Some(lhs), Some(rhs)) = (lhs.get(&tick), rhs.get(&tick)) else {
+            return Some(Divergence { tick, entity: None })
+        };
+
+        let entities: BTreeSet<Entity> = lhs.keys().chain(rhs.keys()).copied().collect();
+
+        entities
+            .into_iter()
+            .find(|entity| lhs.get(entity) != rhs.get(entity))
+            .map(|entity| Divergence {
+                tick,
+                entity: Some(entity),
+            })
+    })
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeterminismReport {
+    pub ticks: usize,
+    /// Divergence between two runs with identical chunking.
+    pub repeated: Option<Divergence>,
+    /// Divergence between runs with different chunking.
+    pub rechunked: Option<Divergence>,
+}
+
+impl DeterminismReport {
+    pub fn is_deterministic(&self) -> bool {
+        self.repeated.is_none() && self.rechunked.is_none()
+    }
+}
+
+impl Display for DeterminismReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Simulated {} ticks", self.ticks)?;
+
+        match self.repeated {
+            Some(divergence) => writeln!(f, "Repeated run diverged at {divergence}")?,
+            None => writeln!(f, "Repeated run matched")?,
+        }
+
+        match self.rechunked {
+            Some(divergence) => write!(f, "Rechunked run diverged at {divergence}"),
+            None => write!(f, "Rechunked run matched"),
+        }
+    }
+}
+
+/// Runs a scene through headless main and physics apps several times,
+/// driving the physics app via the regular fork / join cycle,
+/// and compares the resulting [`TickHashes`].
+pub struct DeterminismHarness {
+    pub ticks: usize,
+    /// Ticks per physics task for the reference and repeated runs.
+    pub chunk_ticks: usize,
+    /// Ticks per physics task for the rechunked run.
+    pub rechunked_ticks: usize,
+    /// Main app updates a run may take before it's considered stalled.
+    pub max_updates: usize,
+    setup: fn(&mut App),
+}
+
+impl DeterminismHarness {
+    /// `setup` configures the main app, spawning bodies and inserting a [`RapierConfiguration`].
+    pub fn new(ticks: usize, setup: fn(&mut App)) -> Self {
+        DeterminismHarness {
+            ticks,
+            chunk_ticks: 16,
+            rechunked_ticks: 7,
+            max_updates: 10_000,
+            setup,
+        }
+    }
+
+    pub fn with_chunks(mut self, chunk_ticks: usize, rechunked_ticks: usize) -> Self {
+        self.chunk_ticks = chunk_ticks;
+        self.rechunked_ticks = rechunked_ticks;
+        self
+    }
+
+    pub fn run(&self) -> DeterminismReport {
+        let reference = self.simulate(self.chunk_ticks);
+        let repeated = self.simulate(self.chunk_ticks);
+        let rechunked = self.simulate(self.rechunked_ticks);
+
+        DeterminismReport {
+            ticks: self.ticks,
+            repeated: first_divergence(&reference, &repeated),
+            rechunked: first_divergence(&reference, &rechunked),
+        }
+    }
+
+    /// Simulate the scene to `self.ticks` in tasks of `chunk_ticks`, returning its log.
+    ///
+    /// Panics if it hasn't got there within `self.max_updates` updates.
+    pub fn simulate(&self, chunk_ticks: usize) -> TickHashes {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(DiagnosticsPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin);
+
+        app.insert_resource(
+            PhysicsAppBuilder::<()>::default()
+                .with_budget(PhysicsBudget::Ticks(chunk_ticks.max(1)))
+                .map(|app| {
+                    app.init_resource::<DeterminismLog>()
+                        .add_system_to_stage(PhysicsStage::PostPhysics, record_determinism);
+                })
+                .build(),
+        )
+        .add_plugin(PhysicsPlugin::<()>::default());
+
+        (self.setup)(&mut app);
+
+        // Park the timeline mid-way through the final tick,
+        // so the target isn't subject to rounding
+        let clock = PhysicsClock::from_config(app.world.resource::<RapierConfiguration>());
+        app.world.spawn(TimelineComponent(Timeline {
+            timestamp: clock.timestamp_at(self.ticks) + clock.dt * 0.5,
+            ..default()
+        }));
+
+        for _ in 0..self.max_updates {
+            app.update();
+
+            // The physics app is only present in the main world between tasks
+            if let Some(physics_app) = app.world.get_resource::<PhysicsApp>() {
+                if physics_app.current_tick() >= self.ticks as isize {
+                    return physics_app.world.resource::<DeterminismLog>().ticks.clone();
+                }
+            }
+
+            // Give the task pool a chance to finish the task before polling it again
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        panic!(
+            "Physics didn't reach tick {} within {} updates",
+            self.ticks, self.max_updates
+        );
+    }
+}
+
+/// A pile of spheres dropped onto the ground at slightly different offsets,
+/// producing plenty of contacts for nondeterminism to show up in.
+pub fn sphere_pile_scene(app: &mut App) {
+    app.insert_resource(RapierConfiguration {
+        gravity: Vect::NEG_Y * 9.81,
+        timestep_mode: TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        },
+        ..default()
+    });
+
+    app.world.spawn((
+        RigidBody::Fixed,
+        Collider::cuboid(20.0, 0.5, 20.0),
+        TransformBundle::default(),
+    ));
+
+    for i in 0..64 {
+        let x = (i % 4) as f32 - 1.5;
+        let z = ((i / 4) % 4) as f32 - 1.5;
+        let y = 2.0 + (i / 16) as f32 * 1.5;
+        let jitter = i as f32 * 0.01;
+
+        app.world.spawn((
+            RigidBody::Dynamic,
+            Collider::ball(0.4),
+            Velocity::default(),
+            TransformBundle::from_transform(Transform::from_translation(Vec3::new(
+                x + jitter,
+                y,
+                z - jitter,
+            ))),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sphere_pile_scene, DeterminismHarness};
+
+    #[test]
+    fn sphere_pile_is_deterministic() {
+        let report = DeterminismHarness::new(120, sphere_pile_scene).run();
+        assert!(report.is_deterministic(), "{report}");
+    }
+}
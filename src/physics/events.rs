@@ -0,0 +1,122 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::event::ManualEventReader,
+    prelude::{Events, Mut, Resource, World},
+};
+use bevy_rapier3d::{pipeline::ContactForceEvent, prelude::CollisionEvent};
+
+use super::world::{DefaultPhysicsWorld, PhysicsWorld};
+
+/// An event produced by the physics simulation,
+/// tagged with the tick and timeline timestamp it occurred at.
+///
+/// Each physics world delivers its own events, distinguished by `W`.
+#[derive(Debug, Clone)]
+pub struct PhysicsEvent<E, W = DefaultPhysicsWorld> {
+    pub tick: usize,
+    pub timestamp: f64,
+    pub event: E,
+    pub world: PhantomData<W>,
+}
+
+/// Physics-world accumulator for the events produced over the course of a task,
+/// so none are lost to [`Events`] double-buffering between ticks.
+///
+/// Events are copied rather than drained, so Rapier's own [`Events`] are still
+/// readable by the world they belong to.
+#[derive(Debug, Default, Resource)]
+pub struct PhysicsEventBuffer {
+    collisions: Vec<PhysicsEvent<CollisionEvent>>,
+    contact_forces: Vec<PhysicsEvent<ContactForceEvent>>,
+    collision_reader: ManualEventReader<CollisionEvent>,
+    contact_force_reader: ManualEventReader<ContactForceEvent>,
+}
+
+impl<E, W> PhysicsEvent<E, W> {
+    fn retag<V>(self) -> PhysicsEvent<E, V> {
+        PhysicsEvent {
+            tick: self.tick,
+            timestamp: self.timestamp,
+            event: self.event,
+            world: PhantomData,
+        }
+    }
+}
+
+impl PhysicsEventBuffer {
+    /// Copy the events produced by the tick that just ran into the buffer.
+    pub fn record(physics_world: &mut World, tick: usize, timestamp: f64) {
+        physics_world.get_resource_or_insert_with(PhysicsEventBuffer::default);
+        physics_world.resource_scope(|physics_world, mut buffer: Mut<PhysicsEventBuffer>| {
+            let buffer = &mut *buffer;
+            read_tagged(
+                physics_world,
+                &mut buffer.collision_reader,
+                &mut buffer.collisions,
+                tick,
+                timestamp,
+            );
+            read_tagged(
+                physics_world,
+                &mut buffer.contact_force_reader,
+                &mut buffer.contact_forces,
+                tick,
+                timestamp,
+            );
+        });
+    }
+
+    /// Send the buffered events to the main world in the order they occurred,
+    /// tagged as coming from the physics world `W`.
+    pub fn deliver<W: PhysicsWorld>(physics_world: &mut World, main_world: &mut World) {
+        let Some(mut buffer) = physics_world.get_resource_mut::<PhysicsEventBuffer>() else {
+            return
+        };
+
+        let collisions = std::mem::take(&mut buffer.collisions);
+        let contact_forces = std::mem::take(&mut buffer.contact_forces);
+
+        main_world
+            .resource_mut::<Events<PhysicsEvent<CollisionEvent, W>>>()
+            .extend(collisions.into_iter().map(PhysicsEvent::retag));
+
+        main_world
+            .resource_mut::<Events<PhysicsEvent<ContactForceEvent, W>>>()
+            .extend(contact_forces.into_iter().map(PhysicsEvent::retag));
+
+        // Events owned by the physics world aren't aged out by the main world's updates
+        if !W::MAIN {
+            if let Some(mut events) = physics_world.get_resource_mut::<Events<CollisionEvent>>() {
+                events.update();
+            }
+
+            if let Some(mut events) = physics_world.get_resource_mut::<Events<ContactForceEvent>>()
+            {
+                events.update();
+            }
+        }
+    }
+}
+
+/// Copy the events sent since `reader` last read them, leaving them in place for other readers.
+fn read_tagged<E>(
+    world: &World,
+    reader: &mut ManualEventReader<E>,
+    buffer: &mut Vec<PhysicsEvent<E>>,
+    tick: usize,
+    timestamp: f64,
+) where
+    E: 'static + Send + Sync + Clone,
+{
+    let Some(events) = world.get_resource::<Events<E>>() else {
+        return
+    };
+
+    buffer.extend(reader.iter(events).map(|event| PhysicsEvent {
+        tick,
+        timestamp,
+        event: event.clone(),
+        world: PhantomData,
+    }));
+}
@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    prelude::{debug, warn, Entity, GlobalTransform, Mut, Resource, Transform, With, World},
+    utils::HashMap,
+};
+use bevy_rapier3d::prelude::{
+    RapierColliderHandle, RapierContext, RapierImpulseJointHandle, RapierMultibodyJointHandle,
+    RapierRigidBodyHandle, Velocity,
+};
+
+/// Periodic snapshots of the physics world, used to step the simulation backwards.
+///
+/// Snapshots are taken after every `interval`th tick,
+/// and discarded once they fall more than `retention` ticks behind the latest one,
+/// except for the first, so the start of the simulation always stays reachable.
+/// Stepping back to a tick between snapshots re-simulates from the one before it.
+#[derive(Debug, Resource)]
+pub struct PhysicsHistory {
+    pub retention: usize,
+    pub interval: usize,
+
+    snapshots: BTreeMap<usize, PhysicsSnapshot>,
+}
+
+impl Default for PhysicsHistory {
+    /// One snapshot a second at Rapier's default 60Hz, kept for ten seconds.
+    fn default() -> Self {
+        PhysicsHistory::new(600, 60)
+    }
+}
+
+/// The serialized [`RapierContext`] of a given tick,
+/// alongside the ECS-side state Rapier would otherwise sync back into it.
+#[derive(Debug, Default, Clone)]
+pub struct PhysicsSnapshot {
+    context: Vec<u8>,
+    bodies: Vec<(Entity, BodySnapshot)>,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct BodySnapshot {
+    transform: Option<Transform>,
+    global_transform: Option<GlobalTransform>,
+    velocity: Option<Velocity>,
+}
+
+impl PhysicsHistory {
+    pub fn new(retention: usize, interval: usize) -> Self {
+        PhysicsHistory {
+            retention,
+            interval: interval.max(1),
+            snapshots: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// The tick of the oldest snapshot.
+    pub fn oldest(&self) -> Option<usize> {
+        self.snapshots.keys().next().copied()
+    }
+
+    /// The tick of the latest snapshot at or before `tick`.
+    pub fn nearest(&self, tick: usize) -> Option<usize> {
+        self.snapshots
+            .range(..=tick)
+            .next_back()
+            .map(|(tick, _)| *tick)
+    }
+
+    /// Snapshot the state of `world` as of `tick`, if it falls on the capture interval.
+    pub fn capture(world: &mut World, tick: usize) {
+        let Some(history) = world.get_resource::<PhysicsHistory>() else {
+            return;
+        };
+
+        if tick % history.interval != 0 {
+            return;
+        }
+
+        let context = match bincode::serialize(world.resource::<RapierContext>()) {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Failed to serialize physics state for tick {tick}: {e}");
+                return;
+            }
+        };
+
+        let bodies = world
+            .query_filtered::<(
+                Entity,
+                Option<&Transform>,
+                Option<&GlobalTransform>,
+                Option<&Velocity>,
+            ), With<RapierRigidBodyHandle>>()
+            .iter(world)
+            .map(|(entity, transform, global_transform, velocity)| {
+                (
+                    entity,
+                    BodySnapshot {
+                        transform: transform.copied(),
+                        global_transform: global_transform.copied(),
+                        velocity: velocity.copied(),
+                    },
+                )
+            })
+            .collect();
+
+        let mut history = world.resource_mut::<PhysicsHistory>();
+        history
+            .snapshots
+            .insert(tick, PhysicsSnapshot { context, bodies });
+
+        let latest = history
+            .snapshots
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or(tick);
+        let oldest = latest.saturating_sub(history.retention);
+        let retained = history.snapshots.split_off(&oldest);
+        let first = std::mem::replace(&mut history.snapshots, retained)
+            .into_iter()
+            .next();
+        history.snapshots.extend(first);
+    }
+
+    /// Restore `world` to the latest snapshot at or before `tick`,
+    /// returning the tick it now represents.
+    ///
+    /// Bodies, colliders and joints whose entity has changed since the snapshot are dropped,
+    /// along with the handles of entities that weren't part of it,
+    /// leaving Rapier to re-create them with fresh entity mappings on its next backend sync.
+    /// Snapshots newer than the restored one are discarded, as they'll be re-simulated.
+    pub fn restore(world: &mut World, tick: usize) -> Result<usize, String> {
+        world.resource_scope(|world, mut history: Mut<PhysicsHistory>| {
+            let snapshot = history.snapshots.range(..=tick).next_back();
+            let Some((&restored_tick, snapshot)) = snapshot else {
+                return Err(format!("No physics snapshot at or before tick {tick}"));
+            };
+
+            let restored: RapierContext = bincode::deserialize(&snapshot.context).map_err(|e| {
+                format!("Failed to deserialize physics state for tick {restored_tick}: {e}")
+            })?;
+
+            debug!("Restoring physics state from tick {restored_tick} for target tick {tick}");
+
+            let mut context = world.resource_mut::<RapierContext>();
+            context.islands = restored.islands;
+            context.broad_phase = restored.broad_phase;
+            context.narrow_phase = restored.narrow_phase;
+            context.bodies = restored.bodies;
+            context.colliders = restored.colliders;
+            context.impulse_joints = restored.impulse_joints;
+            context.multibody_joints = restored.multibody_joints;
+            context.ccd_solver = restored.ccd_solver;
+            context.query_pipeline = restored.query_pipeline;
+            context.integration_parameters = restored.integration_parameters;
+
+            // Restore the ECS-side state so the next sync doesn't clobber the restored bodies
+            for (entity, body) in snapshot.bodies.iter() {
+                let Some(mut entity) = world.get_entity_mut(*entity) else {
+                    continue;
+                };
+
+                if let Some(transform) = body.transform {
+                    entity.insert(transform);
+                }
+
+                if let Some(global_transform) = body.global_transform {
+                    entity.insert(global_transform);
+                }
+
+                if let Some(velocity) = body.velocity {
+                    entity.insert(velocity);
+                }
+            }
+
+            reconcile_handles(world);
+
+            history
+                .snapshots
+                .retain(|&snapshot_tick, _| snapshot_tick <= restored_tick);
+
+            Ok(restored_tick)
+        })
+    }
+}
+
+/// Make the restored Rapier sets and the handles held by entities agree again.
+///
+/// Restored bodies and colliders that no longer belong to the entity holding their handle
+/// (ex. despawned since, or a reused slot) are removed, as are joints whose entity's body moved on.
+/// Entities whose handle then points nowhere lose it, so Rapier's removal detection
+/// clears their stale mapping and its init systems re-create them.
+fn reconcile_handles(world: &mut World) {
+    let body_handles = world
+        .query::<(Entity, &RapierRigidBodyHandle)>()
+        .iter(world)
+        .map(|(entity, handle)| (entity, handle.0))
+        .collect::<HashMap<_, _>>();
+
+    let collider_handles = world
+        .query::<(Entity, &RapierColliderHandle)>()
+        .iter(world)
+        .map(|(entity, handle)| (entity, handle.0))
+        .collect::<HashMap<_, _>>();
+
+    let joint_owners = world
+        .query::<(Entity, &RapierImpulseJointHandle)>()
+        .iter(world)
+        .map(|(entity, handle)| (handle.0, entity))
+        .collect::<HashMap<_, _>>();
+
+    let multibody_joint_handles = world
+        .query::<(Entity, &RapierMultibodyJointHandle)>()
+        .iter(world)
+        .map(|(entity, handle)| (entity, handle.0))
+        .collect::<HashMap<_, _>>();
+
+    let mut context = world.resource_mut::<RapierContext>();
+    let context = &mut *context;
+
+    let stale_bodies = context
+        .bodies
+        .iter()
+        .filter(|(handle, body)| {
+            body_handles.get(&Entity::from_bits(body.user_data as u64)) != Some(handle)
+        })
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+
+    for handle in stale_bodies {
+        context.bodies.remove(
+            handle,
+            &mut context.islands,
+            &mut context.colliders,
+            &mut context.impulse_joints,
+            &mut context.multibody_joints,
+            true,
+        );
+    }
+
+    let stale_colliders = context
+        .colliders
+        .iter()
+        .filter(|(handle, collider)| {
+            collider_handles.get(&Entity::from_bits(collider.user_data as u64)) != Some(handle)
+        })
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+
+    for handle in stale_colliders {
+        context
+            .colliders
+            .remove(handle, &mut context.islands, &mut context.bodies, true);
+    }
+
+    // Joints don't record their entity, but join its body to its parent's
+    let stale_joints = context
+        .impulse_joints
+        .iter()
+        .filter(|(handle, joint)| {
+            joint_owners
+                .get(handle)
+                .and_then(|entity| body_handles.get(entity))
+                != Some(&joint.body2)
+        })
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+
+    for handle in stale_joints {
+        context.impulse_joints.remove(handle, true);
+    }
+
+    let missing_bodies = body_handles
+        .iter()
+        .filter(|(_, handle)| context.bodies.get(**handle).is_none())
+        .map(|(entity, _)| *entity)
+        .collect::<Vec<_>>();
+
+    let missing_colliders = collider_handles
+        .iter()
+        .filter(|(_, handle)| context.colliders.get(**handle).is_none())
+        .map(|(entity, _)| *entity)
+        .collect::<Vec<_>>();
+
+    let missing_joints = joint_owners
+        .iter()
+        .filter(|(handle, _)| context.impulse_joints.get(**handle).is_none())
+        .map(|(_, entity)| *entity)
+        .collect::<Vec<_>>();
+
+    let missing_multibody_joints = multibody_joint_handles
+        .iter()
+        .filter(|(_, handle)| context.multibody_joints.get(**handle).is_none())
+        .map(|(entity, _)| *entity)
+        .collect::<Vec<_>>();
+
+    for entity in missing_bodies {
+        world.entity_mut(entity).remove::<RapierRigidBodyHandle>();
+    }
+
+    for entity in missing_colliders {
+        world.entity_mut(entity).remove::<RapierColliderHandle>();
+    }
+
+    for entity in missing_joints {
+        world
+            .entity_mut(entity)
+            .remove::<RapierImpulseJointHandle>();
+    }
+
+    for entity in missing_multibody_joints {
+        world
+            .entity_mut(entity)
+            .remove::<RapierMultibodyJointHandle>();
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    pbr::MeshUniform,
+    prelude::{
+        AnyOf, Commands, Component, Entity, GlobalTransform, Parent, Quat, Query, Res, Transform,
+        Vec3,
+    },
+    render::{extract_component::ExtractComponent as ExtractRenderComponent, Extract},
+};
+use bevy_rapier3d::prelude::Velocity;
+
+use crate::timeline::TimelineComponent;
+
+use super::{clock::PhysicsClock, world::PhysicsWorld, world_timeline, PhysicsApp, PhysicsTask};
+
+/// How a [`LerpTransform`] turns its history into a transform for the current timestamp.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum InterpolationMode {
+    /// Lerp / slerp between the two samples surrounding the timestamp.
+    #[default]
+    Linear,
+    /// Catmull-Rom spline through the surrounding four samples.
+    CatmullRom,
+    /// Cubic Hermite spline using sampled velocities as tangents,
+    /// falling back to Catmull-Rom tangents for samples without one.
+    Hermite,
+    /// Project the newest sample forward using its velocity instead of lagging behind.
+    Extrapolate,
+}
+
+/// A single physics tick's worth of transform history.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct LerpSample {
+    pub timestamp: f64,
+    pub transform: Transform,
+    /// Linear displacement per tick, if the entity has a velocity.
+    pub linear: Option<Vec3>,
+    /// Angular displacement per tick as a scaled axis, if the entity has a velocity.
+    pub angular: Option<Vec3>,
+}
+
+/// Transform history of a physics entity, used to smooth out its motion between ticks.
+///
+/// Samples are stored newest first, one per tick.
+/// Non-extrapolating modes display the entity one tick behind the timeline,
+/// so there is always a pair of samples to interpolate between.
+#[derive(Debug, Clone, Component)]
+pub struct LerpTransform {
+    pub samples: VecDeque<LerpSample>,
+    /// Number of samples to retain; interpolation needs at least 2, splines 4.
+    pub history: usize,
+    pub mode: InterpolationMode,
+}
+
+impl Default for LerpTransform {
+    fn default() -> Self {
+        LerpTransform {
+            samples: VecDeque::default(),
+            history: 4,
+            mode: InterpolationMode::default(),
+        }
+    }
+}
+
+impl LerpTransform {
+    pub fn with_mode(mut self, mode: InterpolationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_history(mut self, history: usize) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn push(&mut self, sample: LerpSample) {
+        // Stepping backwards invalidates any samples from the future
+        while self
+            .samples
+            .front()
+            .map(|front| front.timestamp >= sample.timestamp)
+            .unwrap_or_default()
+        {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_front(sample);
+        self.samples.truncate(self.history.max(2));
+    }
+
+    /// The transform to display at `timestamp`,
+    /// or `None` if there isn't enough history to produce one.
+    pub fn sample(&self, timestamp: f64) -> Option<Transform> {
+        let newest = self.samples.get(0)?;
+        let prev = self.samples.get(1)?;
+
+        let duration = newest.timestamp - prev.timestamp;
+        if duration <= 0.0 {
+            return None;
+        }
+
+        if self.mode == InterpolationMode::Extrapolate && timestamp > newest.timestamp {
+            return Some(extrapolate(
+                newest,
+                prev,
+                (timestamp - newest.timestamp) / duration,
+            ));
+        }
+
+        let timestamp = match self.mode {
+            InterpolationMode::Extrapolate => timestamp,
+            _ => timestamp - duration,
+        };
+
+        // Find the segment containing the timestamp, clamping to either end of the history
+        if timestamp >= newest.timestamp {
+            return Some(newest.transform);
+        }
+
+        let Some(i) =
+            (0..self.samples.len() - 1).find(|i| self.samples[i + 1].timestamp <= timestamp)
+        else {
+            return self.samples.back().map(|sample| sample.transform);
+        };
+
+        let to = &self.samples[i];
+        let from = &self.samples[i + 1];
+        let t = ((timestamp - from.timestamp) / (to.timestamp - from.timestamp)) as f32;
+
+        let before = self.samples.get(i + 2).unwrap_or(from);
+        let after = if i > 0 { &self.samples[i - 1] } else { to };
+
+        let transform = match self.mode {
+            InterpolationMode::Linear | InterpolationMode::Extrapolate => {
+                interpolate_transform(&from.transform, &to.transform, t)
+            }
+            InterpolationMode::CatmullRom => Transform {
+                translation: catmull_rom(
+                    before.transform.translation,
+                    from.transform.translation,
+                    to.transform.translation,
+                    after.transform.translation,
+                    t,
+                ),
+                ..interpolate_transform(&from.transform, &to.transform, t)
+            },
+            InterpolationMode::Hermite => {
+                let from_tangent = from.linear.unwrap_or_else(|| {
+                    (to.transform.translation - before.transform.translation) * 0.5
+                });
+                let to_tangent = to.linear.unwrap_or_else(|| {
+                    (after.transform.translation - from.transform.translation) * 0.5
+                });
+
+                Transform {
+                    translation: hermite(
+                        from.transform.translation,
+                        from_tangent,
+                        to.transform.translation,
+                        to_tangent,
+                        t,
+                    ),
+                    ..interpolate_transform(&from.transform, &to.transform, t)
+                }
+            }
+        };
+
+        Some(transform)
+    }
+}
+
+/// Main-world copy of an entity's interpolated transform, updated every frame from its [`LerpTransform`].
+///
+/// Opt-in per entity, for systems that need to see smoothed motion (ex. camera follow);
+/// entities without one are only interpolated in the render world.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct InterpolatedTransform {
+    pub local: Transform,
+    pub global: GlobalTransform,
+}
+
+impl ExtractRenderComponent for InterpolatedTransform {
+    type Query = &'static Self;
+
+    type Filter = ();
+
+    fn extract_component(item: bevy::ecs::query::QueryItem<'_, Self::Query>) -> Self {
+        *item
+    }
+}
+
+/// Render-world copy of a [`LerpTransform`], along with the timestamp to sample it at.
+#[derive(Debug, Clone, Component)]
+pub struct ExtractedLerpTransform {
+    pub lerp_transform: LerpTransform,
+    /// Timestamp of the timeline followed by the entity's physics world.
+    pub timestamp: f64,
+    /// Global transform of the entity's parent, as samples are relative to it.
+    pub parent: Option<GlobalTransform>,
+}
+
+pub fn interpolate_transform(from: &Transform, to: &Transform, t: f32) -> Transform {
+    let translation = from.translation.lerp(to.translation, t);
+    let rotation = from.rotation.slerp(to.rotation, t);
+    let scale = from.scale.lerp(to.scale, t);
+
+    Transform {
+        translation,
+        rotation,
+        scale,
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn hermite(p0: Vec3, m0: Vec3, p1: Vec3, m1: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (2.0 * t3 - 3.0 * t2 + 1.0) * p0
+        + (t3 - 2.0 * t2 + t) * m0
+        + (-2.0 * t3 + 3.0 * t2) * p1
+        + (t3 - t2) * m1
+}
+
+/// Project `newest` forward by `ticks`, using its velocity if it has one
+/// or the motion since `prev` otherwise.
+fn extrapolate(newest: &LerpSample, prev: &LerpSample, ticks: f64) -> Transform {
+    let ticks = ticks as f32;
+
+    let linear = newest
+        .linear
+        .unwrap_or(newest.transform.translation - prev.transform.translation);
+
+    let rotation = match newest.angular {
+        Some(angular) => Quat::from_scaled_axis(angular * ticks) * newest.transform.rotation,
+        None => {
+            let delta = newest.transform.rotation * prev.transform.rotation.inverse();
+            Quat::IDENTITY.slerp(delta, ticks) * newest.transform.rotation
+        }
+    };
+
+    Transform {
+        translation: newest.transform.translation + linear * ticks,
+        rotation,
+        scale: newest.transform.scale,
+    }
+}
+
+pub fn update_lerp_transform(
+    clock: Res<PhysicsClock>,
+    mut query_lerp_transform: Query<(&Transform, Option<&Velocity>, &mut LerpTransform)>,
+) {
+    let timestamp = clock.timestamp();
+    let dt = clock.dt as f32;
+
+    for (transform, velocity, mut lerp_transform) in query_lerp_transform.iter_mut() {
+        lerp_transform.push(LerpSample {
+            timestamp,
+            transform: *transform,
+            linear: velocity.map(|velocity| velocity.linvel * dt),
+            angular: velocity.map(|velocity| velocity.angvel * dt),
+        });
+    }
+}
+
+pub fn interpolate_main_world<W: PhysicsWorld>(
+    physics_app: Option<Res<PhysicsApp<W>>>,
+    physics_task: Option<Res<PhysicsTask<W>>>,
+    query_timeline: Query<&TimelineComponent>,
+    query_parent: Query<&GlobalTransform>,
+    mut query_interpolated: Query<
+        (
+            &Transform,
+            &LerpTransform,
+            Option<&Parent>,
+            &mut InterpolatedTransform,
+        ),
+        W::Membership,
+    >,
+) {
+    let Some(timeline) = world_timeline(
+        physics_app.as_deref(),
+        physics_task.as_deref(),
+        &query_timeline,
+    ) else {
+        return
+    };
+
+    for (transform, lerp_transform, parent, mut interpolated) in query_interpolated.iter_mut() {
+        let local = lerp_transform
+            .sample(timeline.timestamp)
+            .unwrap_or(*transform);
+
+        let global = match parent.and_then(|parent| query_parent.get(parent.get()).ok()) {
+            Some(parent) => parent.mul_transform(local),
+            None => local.into(),
+        };
+
+        *interpolated = InterpolatedTransform { local, global };
+    }
+}
+
+/// Copy the [`LerpTransform`]s of `W`'s members into the render world,
+/// to be sampled at the timestamp of the timeline `W` follows.
+pub fn extract_lerp_transforms<W: PhysicsWorld>(
+    mut commands: Commands,
+    physics_app: Extract<Option<Res<PhysicsApp<W>>>>,
+    physics_task: Extract<Option<Res<PhysicsTask<W>>>>,
+    query_timeline: Extract<Query<&TimelineComponent>>,
+    query_parent: Extract<Query<&GlobalTransform>>,
+    query_lerp_transform: Extract<Query<(Entity, &LerpTransform, Option<&Parent>), W::Membership>>,
+) {
+    let Some(timeline) = world_timeline(
+        physics_app.as_deref(),
+        physics_task.as_deref(),
+        &query_timeline,
+    ) else {
+        return
+    };
+
+    let extracted: Vec<_> = query_lerp_transform
+        .iter()
+        .map(|(entity, lerp_transform, parent)| {
+            let extracted = ExtractedLerpTransform {
+                lerp_transform: lerp_transform.clone(),
+                timestamp: timeline.timestamp,
+                parent: parent.and_then(|parent| query_parent.get(parent.get()).ok().copied()),
+            };
+
+            (entity, extracted)
+        })
+        .collect();
+
+    commands.insert_or_spawn_batch(extracted);
+}
+
+pub fn interpolate_physics(
+    mut query_lerp_transform: Query<(
+        &mut MeshUniform,
+        AnyOf<(&ExtractedLerpTransform, &InterpolatedTransform)>,
+    )>,
+) {
+    for (mut mesh_uniform, (extracted, interpolated)) in query_lerp_transform.iter_mut() {
+        // Reuse the main world's result where there is one
+        let matrix = match (interpolated, extracted) {
+            (Some(interpolated), _) => interpolated.global.compute_matrix(),
+            (None, Some(extracted)) => {
+                let Some(trx) = extracted.lerp_transform.sample(extracted.timestamp) else {
+                    continue
+                };
+
+                // Samples are local, like the transforms they were taken from
+                match extracted.parent {
+                    Some(parent) => parent.mul_transform(trx).compute_matrix(),
+                    None => trx.compute_matrix(),
+                }
+            }
+            (None, None) => continue,
+        };
+
+        mesh_uniform.transform = matrix;
+        mesh_uniform.inverse_transpose_model = mesh_uniform.transform.inverse().transpose();
+    }
+}
@@ -0,0 +1,1622 @@
+use std::{
+    any::TypeId,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::{AppExit, ScheduleRunnerPlugin},
+    ecs::{query::WorldQuery, schedule::ShouldRun},
+    prelude::{
+        default, AddAsset, App, AppTypeRegistry, AssetPlugin, CoreStage, IntoSystemDescriptor,
+        Mesh, Or, Plugin, Res, ResMut, StageLabel, StartupSchedule, StartupStage, SystemStage,
+        With, Without,
+    },
+    render::{
+        extract_component::ExtractComponentPlugin as ExtractRenderComponentPlugin, RenderApp,
+        RenderStage,
+    },
+    scene::Scene,
+    time::{Time, TimePlugin, TimeUpdateStrategy},
+    transform::TransformSystem,
+};
+use bevy_rapier3d::prelude::{
+    ActiveCollisionTypes, ActiveEvents, ActiveHooks, AdditionalMassProperties, Ccd, Collider,
+    ColliderDisabled, ColliderMassProperties, CollisionGroups, ContactForceEventThreshold, Damping,
+    Dominance, ExternalForce, Friction, GravityScale, ImpulseJoint, KinematicCharacterController,
+    KinematicCharacterControllerOutput, LockedAxes, MultibodyJoint, PhysicsStages, RapierContext,
+    RapierImpulseJointHandle, RapierMultibodyJointHandle, RapierPhysicsPlugin, ReadMassProperties,
+    Restitution, RigidBody, RigidBodyDisabled, Sensor, Sleeping, SolverGroups,
+    TransformInterpolation, Velocity,
+};
+
+use std::ops::{Deref, DerefMut};
+
+use bevy::{
+    prelude::{
+        debug, error, BuildChildren, Commands, Entity, Events, GlobalTransform, Local, Parent,
+        Query, Resource, Schedule, Stage, Transform, World,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+    utils::HashSet,
+};
+use bevy_rapier3d::{
+    pipeline::ContactForceEvent,
+    prelude::{
+        CollisionEvent, PhysicsHooksWithQueryResource, RapierColliderHandle, RapierConfiguration,
+        RapierRigidBodyHandle, SimulationToRenderTime,
+    },
+};
+
+use crate::timeline::TimelineComponent;
+
+use crate::physics_component;
+
+use self::{
+    changes::PhysicsChanges,
+    character::{
+        apply_pending_translations, pend_translations, queue_translations,
+        spread_pending_translations,
+    },
+    clock::{tick_timeline, PhysicsClock},
+    component::{PhysicsComponentAppExt, PhysicsDriven},
+    events::{PhysicsEvent, PhysicsEventBuffer},
+    extract_param::Extract,
+    history::PhysicsHistory,
+    interpolation::{
+        extract_lerp_transforms, interpolate_main_world, interpolate_physics,
+        update_lerp_transform, InterpolatedTransform,
+    },
+    prediction::{build_prediction_app, PhysicsPredictionPlugin, PhysicsPredictions},
+    progress::{PhysicsBudget, PhysicsDiagnostics, PhysicsProgress},
+    query::PhysicsQuery,
+    removal::{PhysicsRemovals, RapierRemovalPlugin},
+    world::{
+        despawn_stale_entities, detach_orphan_joints, exclude_other_world_members,
+        DefaultPhysicsWorld, Mirrored, OrphanJoint, OtherPhysicsWorlds, PhysicsWorld,
+    },
+};
+
+pub use interpolation::LerpTransform;
+
+pub mod changes;
+pub mod character;
+pub mod clock;
+pub mod component;
+pub mod debug;
+pub mod determinism;
+pub mod events;
+pub mod history;
+pub mod interpolation;
+pub mod prediction;
+pub mod progress;
+pub mod query;
+pub mod removal;
+pub mod world;
+
+/// Drives the [`PhysicsApp`] of the physics world `W`.
+///
+/// Add once per world, alongside its [`PhysicsApp`] resource.
+/// The [`DefaultPhysicsWorld`]'s plugin also sets up Rapier and interpolation for the main world.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PhysicsPlugin<T = (), W = DefaultPhysicsWorld> {
+    _phantom: PhantomData<(T, W)>,
+}
+
+pub struct PhysicsAppBuilder<T, W = DefaultPhysicsWorld> {
+    app: App,
+    budget: PhysicsBudget,
+    /// Applied again to the app predictions are simulated in.
+    maps: Vec<Box<dyn Fn(&mut App)>>,
+    phantom: PhantomData<(T, W)>,
+}
+
+impl<T, W> Default for PhysicsAppBuilder<T, W>
+where
+    T: 'static + Send + Sync + WorldQuery,
+    W: PhysicsWorld,
+{
+    fn default() -> Self {
+        // Create the physics app
+        let mut app = App::empty();
+
+        // Setup the extraction for reading data from the main world to the physics world
+        let mut extract_stage = SystemStage::single_threaded()
+            .with_system(extract_timeline)
+            .with_system(extract_hierarchy::<W>);
+
+        app.init_resource::<MainWorld>();
+        app.world.remove_resource::<MainWorld>();
+        let main_world_in_render = app
+            .world
+            .components()
+            .get_resource_id(TypeId::of::<MainWorld>());
+
+        // `Extract` systems must read from the main world. We want to emit an error when that doesn't occur
+        // Safe to unwrap: Ensured it existed just above
+        extract_stage.set_must_read_resource(main_world_in_render.unwrap());
+
+        // don't apply buffers when the stage finishes running
+        // extract stage runs on the render world, but buffers are applied
+        // after access to the main world is removed
+        // See also https://github.com/bevyengine/bevy/issues/5082
+        extract_stage.set_apply_buffers(false);
+
+        // Setup the writeback stage for reading data from the physics world to the main world
+        let mut writeback_stage = SystemStage::single_threaded();
+
+        // don't apply buffers when the stage finishes running,
+        // as they're instead applied to the main world
+        writeback_stage.set_apply_buffers(false);
+
+        app.schedule
+            .add_stage(
+                StartupSchedule,
+                Schedule::default()
+                    .with_run_criteria(ShouldRun::once)
+                    .with_stage(StartupStage::Startup, SystemStage::single_threaded()),
+            )
+            .add_stage(
+                CoreStage::First,
+                SystemStage::single_threaded().with_system(tick_timeline),
+            )
+            .add_stage(CoreStage::PreUpdate, SystemStage::single_threaded())
+            .add_stage(PhysicsStage::Extract, extract_stage)
+            .add_stage(
+                PhysicsStage::PrePhysics,
+                SystemStage::single_threaded().with_system(apply_pending_translations),
+            )
+            .add_stage(
+                PhysicsStage::RapierSyncBackend,
+                SystemStage::single_threaded().with_system_set(
+                    RapierPhysicsPlugin::<T>::get_systems(PhysicsStages::SyncBackend),
+                ),
+            )
+            .add_stage(
+                PhysicsStage::RapierStepSimulation,
+                SystemStage::single_threaded().with_system_set(
+                    RapierPhysicsPlugin::<T>::get_systems(PhysicsStages::StepSimulation),
+                ),
+            )
+            .add_stage(
+                PhysicsStage::RapierWriteback,
+                SystemStage::single_threaded().with_system_set(
+                    RapierPhysicsPlugin::<T>::get_systems(PhysicsStages::Writeback),
+                ),
+            )
+            .add_stage(
+                PhysicsStage::RapierDetectDespawn,
+                SystemStage::single_threaded().with_system_set(
+                    RapierPhysicsPlugin::<T>::get_systems(PhysicsStages::DetectDespawn),
+                ),
+            )
+            .add_stage(
+                PhysicsStage::PostPhysics,
+                SystemStage::single_threaded().with_system(update_lerp_transform),
+            )
+            .add_stage(PhysicsStage::Writeback, writeback_stage)
+            .add_stage(CoreStage::PostUpdate, SystemStage::single_threaded())
+            .add_stage(
+                CoreStage::Last,
+                SystemStage::single_threaded().with_system(World::clear_trackers),
+            );
+
+        app.add_event::<AppExit>();
+        app.init_resource::<AppTypeRegistry>();
+
+        // Configure it with the minimum setup needed to run rapier
+        app.add_plugin(TimePlugin::default());
+        app.insert_resource(TimeUpdateStrategy::ManualInstant(
+            app.world.resource::<Time>().startup(),
+        ));
+
+        app.add_plugin(ScheduleRunnerPlugin::default());
+
+        app.add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_asset::<Scene>();
+
+        // Predictions extract from the physics world in place of the main world
+        app.init_resource::<ScratchMainWorld>();
+
+        app.init_resource::<PhysicsClock>()
+            .init_resource::<PhysicsChanges>()
+            .init_resource::<PhysicsHistory>()
+            .init_resource::<PhysicsEventBuffer>();
+
+        // The main world's Rapier resources are moved in for each task,
+        // other worlds own theirs outright
+        if !W::MAIN {
+            init_rapier_resources::<T>(&mut app);
+        }
+
+        PhysicsAppBuilder {
+            app,
+            budget: default(),
+            maps: default(),
+            phantom: default(),
+        }
+    }
+}
+
+impl<T, W> PhysicsAppBuilder<T, W>
+where
+    W: PhysicsWorld,
+{
+    /// Configure the physics app, ex. adding systems to its [`PhysicsStage`]s.
+    /// `f` is applied to the app predictions are simulated in as well.
+    pub fn map<F: 'static + Fn(&mut App)>(mut self, f: F) -> Self {
+        f(&mut self.app);
+        self.maps.push(Box::new(f));
+        self
+    }
+
+    /// Configure how many ticks of [`PhysicsHistory`] are retained for backward stepping,
+    /// and how many ticks apart its snapshots are taken.
+    pub fn with_history(mut self, retention: usize, interval: usize) -> Self {
+        self.app
+            .insert_resource(PhysicsHistory::new(retention, interval));
+        self
+    }
+
+    /// Configure how much simulation each physics task may perform before writing back.
+    pub fn with_budget(mut self, budget: PhysicsBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+}
+
+impl<T, W> PhysicsAppBuilder<T, W>
+where
+    T: 'static + Send + Sync + WorldQuery,
+    W: PhysicsWorld,
+{
+    pub fn build(mut self) -> PhysicsApp<W> {
+        let prediction_app = build_prediction_app::<T, W>(&self.maps);
+        self.app
+            .insert_resource(PhysicsPredictions::<W>::new(prediction_app));
+
+        PhysicsApp {
+            budget: self.budget,
+            ..PhysicsApp::from(self.app)
+        }
+    }
+
+    /// Build a [`PhysicsApp`] that runs on its own, without a main app or render app.
+    ///
+    /// It owns its Rapier resources and has already run its startup systems,
+    /// so entities can be spawned directly into its world and simulated
+    /// with [`PhysicsApp::advance`].
+    pub fn build_headless(mut self) -> PhysicsApp<W> {
+        init_rapier_resources::<T>(&mut self.app);
+
+        let mut physics_app = self.build();
+        PhysicsClock::sync(&mut physics_app.world);
+        physics_app.run_startup();
+        physics_app
+    }
+}
+
+/// Rapier resources for physics worlds that don't borrow them from the main world.
+/// Existing resources, such as a [`RapierConfiguration`] inserted via [`PhysicsAppBuilder::map`], are kept.
+fn init_rapier_resources<T: 'static + Send + Sync + WorldQuery>(app: &mut App) {
+    app.init_resource::<RapierContext>()
+        .init_resource::<RapierConfiguration>()
+        .init_resource::<SimulationToRenderTime>()
+        .add_event::<CollisionEvent>()
+        .add_event::<ContactForceEvent>();
+
+    if !app
+        .world
+        .contains_resource::<PhysicsHooksWithQueryResource<T>>()
+    {
+        app.insert_resource(PhysicsHooksWithQueryResource::<T>(Box::new(())));
+    }
+}
+
+impl<T, W> Plugin for PhysicsPlugin<T, W>
+where
+    T: 'static + Send + Sync + WorldQuery,
+    W: PhysicsWorld,
+{
+    fn build(&self, app: &mut bevy::prelude::App) {
+        if W::MAIN {
+            app.add_plugin(RapierPhysicsPlugin::<T>::default().with_default_system_setup(false));
+
+            app.add_plugin(ExtractRenderComponentPlugin::<InterpolatedTransform>::default());
+
+            // Headless apps have no render world to interpolate
+            if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+                render_app
+                    .add_system_to_stage(RenderStage::Prepare, interpolate_physics.at_start());
+            }
+
+            app.add_startup_system(PhysicsDiagnostics::setup_system)
+                .add_system_to_stage(
+                    CoreStage::PostUpdate,
+                    PhysicsDiagnostics::diagnostic_system::<W>,
+                );
+        }
+
+        // Each world's members are interpolated against the timeline it follows
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            interpolate_main_world::<W>.after(TransformSystem::TransformPropagate),
+        );
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_system_to_stage(RenderStage::Extract, extract_lerp_transforms::<W>);
+        }
+
+        app.init_resource::<ScratchMainWorld>();
+
+        if !W::MAIN {
+            app.init_resource::<OtherPhysicsWorlds>();
+            app.world
+                .resource_mut::<OtherPhysicsWorlds>()
+                .register::<W>();
+        }
+
+        app.add_system(fork_physics::<T, W>.at_end())
+            .add_system(join_physics::<T, W>.at_end().after(fork_physics::<T, W>));
+
+        app.add_system(dispatch_physics::<W>);
+
+        // Collects the translations requested during the frame, ahead of the fork handing them over
+        app.add_system(
+            queue_translations::<W>
+                .at_end()
+                .before(fork_physics::<T, W>),
+        );
+
+        app.add_event::<PhysicsEvent<CollisionEvent, W>>()
+            .add_event::<PhysicsEvent<ContactForceEvent, W>>();
+
+        app.init_resource::<PhysicsProgress<W>>()
+            .init_resource::<PhysicsQuery<W>>();
+
+        app.register_physics_component_in::<RigidBody, W>()
+            .register_physics_component_in::<Transform, W>()
+            .register_physics_component_in::<GlobalTransform, W>()
+            .register_physics_component_in::<TransformInterpolation, W>()
+            .register_physics_component_in::<Velocity, W>()
+            .register_physics_component_in::<AdditionalMassProperties, W>()
+            .register_physics_component_in::<ReadMassProperties, W>()
+            .register_physics_component_in::<LockedAxes, W>()
+            .register_physics_component_in::<ExternalForce, W>()
+            .register_physics_component_in::<GravityScale, W>()
+            .register_physics_component_in::<Ccd, W>()
+            .register_physics_component_in::<Dominance, W>()
+            .register_physics_component_in::<Sleeping, W>()
+            .register_physics_component_in::<Damping, W>()
+            .register_physics_component_in::<RigidBodyDisabled, W>()
+            .register_physics_component_in::<LerpTransform, W>()
+            .register_physics_component_in::<PhysicsDriven, W>();
+
+        app.register_physics_component_in::<Collider, W>()
+            .register_physics_component_in::<Sensor, W>()
+            .register_physics_component_in::<ColliderMassProperties, W>()
+            .register_physics_component_in::<ActiveEvents, W>()
+            .register_physics_component_in::<ActiveHooks, W>()
+            .register_physics_component_in::<ActiveCollisionTypes, W>()
+            .register_physics_component_in::<Friction, W>()
+            .register_physics_component_in::<Restitution, W>()
+            .register_physics_component_in::<CollisionGroups, W>()
+            .register_physics_component_in::<SolverGroups, W>()
+            .register_physics_component_in::<ContactForceEventThreshold, W>()
+            .register_physics_component_in::<ColliderDisabled, W>();
+
+        app.register_physics_component_in::<ImpulseJoint, W>()
+            .register_physics_component_in::<MultibodyJoint, W>();
+
+        app.register_physics_component_in::<KinematicCharacterController, W>()
+            .register_physics_component_in::<KinematicCharacterControllerOutput, W>();
+
+        // Handles only round-trip through the main world for the world owning its context,
+        // other worlds keep theirs on their own persistent entities
+        if W::MAIN {
+            app.register_physics_component_in::<RapierRigidBodyHandle, W>()
+                .register_physics_component_in::<RapierColliderHandle, W>()
+                .register_physics_component_in::<RapierImpulseJointHandle, W>()
+                .register_physics_component_in::<RapierMultibodyJointHandle, W>();
+        }
+
+        app.add_plugin(RapierRemovalPlugin::<W>::default())
+            .add_plugin(PhysicsPredictionPlugin::<W>::default());
+
+        app.add_startup_system(|world: &mut World| {
+            let mut physics_app = world.remove_resource::<PhysicsApp<W>>().unwrap();
+
+            // Dispatch needs the tick rate before the first task has synced it
+            if W::MAIN {
+                let clock = PhysicsClock::from_config(world.resource::<RapierConfiguration>());
+                physics_app.world.insert_resource(clock);
+            } else {
+                PhysicsClock::sync(&mut physics_app.world);
+            }
+
+            physics_app.run_startup();
+
+            world.insert_resource(physics_app);
+        });
+    }
+}
+
+type RigidBodyOrCollider = Or<(With<RigidBody>, With<Collider>)>;
+type Simulated = Or<(
+    With<RigidBody>,
+    With<Collider>,
+    With<KinematicCharacterController>,
+)>;
+type SimulatedOrDriven = Or<(
+    With<RigidBody>,
+    With<Collider>,
+    With<KinematicCharacterController>,
+    With<PhysicsDriven>,
+)>;
+/// Entities whose transform the physics world is authoritative for.
+type Moved = Or<(
+    With<RigidBody>,
+    With<KinematicCharacterController>,
+    With<PhysicsDriven>,
+)>;
+
+physics_component!(RigidBody: extract(()));
+physics_component!(Transform: extract(SimulatedOrDriven), writeback(Moved));
+physics_component!(GlobalTransform: extract(Simulated));
+physics_component!(TransformInterpolation: writeback(With<RigidBody>));
+physics_component!(Velocity: extract(With<RigidBody>), writeback(With<RigidBody>));
+physics_component!(AdditionalMassProperties: extract(With<RigidBody>));
+physics_component!(ReadMassProperties: extract(With<RigidBody>));
+physics_component!(LockedAxes: extract(With<RigidBody>));
+physics_component!(ExternalForce: extract(With<RigidBody>));
+physics_component!(GravityScale: extract(With<RigidBody>));
+physics_component!(Ccd: extract(With<RigidBody>));
+physics_component!(Dominance: extract(With<RigidBody>));
+physics_component!(Sleeping: extract(With<RigidBody>), writeback(With<RigidBody>));
+physics_component!(Damping: extract(With<RigidBody>));
+physics_component!(RigidBodyDisabled: extract(With<RigidBody>));
+physics_component!(RapierRigidBodyHandle: extract(With<RigidBody>), writeback(With<RigidBody>));
+physics_component!(LerpTransform: extract(Moved), writeback(Moved));
+
+physics_component!(PhysicsDriven: extract(()));
+
+physics_component!(Collider: extract(()));
+physics_component!(Sensor: extract(With<Collider>));
+physics_component!(ColliderMassProperties: extract(With<Collider>));
+physics_component!(ActiveEvents: extract(With<Collider>));
+physics_component!(ActiveHooks: extract(With<Collider>));
+physics_component!(ActiveCollisionTypes: extract(With<Collider>));
+physics_component!(Friction: extract(With<Collider>));
+physics_component!(Restitution: extract(With<Collider>));
+physics_component!(CollisionGroups: extract(With<Collider>));
+physics_component!(SolverGroups: extract(With<Collider>));
+physics_component!(ContactForceEventThreshold: extract(With<Collider>));
+physics_component!(ColliderDisabled: extract(With<Collider>));
+physics_component!(RapierColliderHandle: extract(With<Collider>), writeback(With<Collider>));
+
+// Joints reference their parent body by entity, which needs no mapping
+// as physics-world entities share their main-world counterpart's id.
+// Those whose parent is simulated elsewhere, or not at all, are skipped (see `OrphanJoint`).
+// Changes made to them by physics-side systems, such as animated motors, are written back
+type Joint = (With<RigidBody>, Without<OrphanJoint>);
+
+physics_component!(ImpulseJoint: extract(Joint), writeback(With<RigidBody>));
+physics_component!(MultibodyJoint: extract(Joint), writeback(With<RigidBody>));
+physics_component!(RapierImpulseJointHandle: extract(With<ImpulseJoint>), writeback(With<ImpulseJoint>));
+physics_component!(RapierMultibodyJointHandle: extract(With<MultibodyJoint>), writeback(With<MultibodyJoint>));
+
+// Translations requested in the main world are queued rather than extracted,
+// then spread over the ticks of the next task (see `character`).
+// Rapier moves controllers during the backend sync, and clears their translation once applied
+physics_component!(KinematicCharacterController: extract(()));
+physics_component!(KinematicCharacterControllerOutput: writeback(With<KinematicCharacterController>));
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, StageLabel)]
+pub enum PhysicsStage {
+    Extract,
+    PrePhysics,
+    RapierSyncBackend,
+    RapierStepSimulation,
+    RapierWriteback,
+    RapierDetectDespawn,
+    PostPhysics,
+    Writeback,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct PhysicsApp<W = DefaultPhysicsWorld> {
+    pub world: World,
+    pub schedule: Schedule,
+    pub target_tick: usize,
+    pub budget: PhysicsBudget,
+    /// Timeline entity this world follows, or the only timeline if `None`.
+    pub timeline: Option<Entity>,
+
+    current_tick: Option<usize>,
+    chunk_ticks: usize,
+    chunk_duration: Duration,
+    phantom: PhantomData<W>,
+}
+
+impl<W> From<App> for PhysicsApp<W> {
+    fn from(value: App) -> Self {
+        PhysicsApp {
+            world: value.world,
+            schedule: value.schedule,
+            current_tick: None,
+            target_tick: 0,
+            budget: default(),
+            timeline: None,
+            chunk_ticks: 0,
+            chunk_duration: Duration::ZERO,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<W: PhysicsWorld> PhysicsApp<W> {
+    pub fn current_tick(&self) -> isize {
+        self.current_tick
+            .map(|current_tick| current_tick as isize)
+            .unwrap_or(-1)
+    }
+
+    pub fn clock(&self) -> PhysicsClock {
+        *self.world.resource::<PhysicsClock>()
+    }
+
+    fn update_progress(&self, progress: &mut PhysicsProgress<W>) {
+        progress.current_tick = self.current_tick();
+        progress.target_tick = self.target_tick;
+        progress.ticks_remaining = self.target_tick.abs_diff(self.current_tick().max(0) as usize);
+        progress.chunk_ticks = self.chunk_ticks;
+        progress.chunk_duration = self.chunk_duration;
+        progress.extracted_entities = self
+            .world
+            .get_resource::<PhysicsChanges>()
+            .map(|changes| changes.extracted.len())
+            .unwrap_or_default();
+    }
+
+    /// Run the physics app's startup systems.
+    pub fn run_startup(&mut self) {
+        let schedule = self
+            .schedule
+            .get_stage_mut::<Schedule>(StartupSchedule)
+            .unwrap();
+
+        let stage = schedule
+            .get_stage_mut::<SystemStage>(StartupStage::Startup)
+            .unwrap();
+
+        stage.run(&mut self.world);
+    }
+
+    fn run_stage(&mut self, label: impl StageLabel) {
+        self.schedule
+            .get_stage_mut::<SystemStage>(label)
+            .unwrap()
+            .run(&mut self.world);
+    }
+
+    /// Simulate a single tick forward, snapshotting the result into [`PhysicsHistory`].
+    pub fn step(&mut self) {
+        let tick = (self.current_tick() + 1) as usize;
+
+        let timestamp = {
+            let mut clock = self.world.resource_mut::<PhysicsClock>();
+            clock.tick = tick;
+            clock.timestamp()
+        };
+
+        let startup = self.world.resource::<Time>().startup();
+        let instant = startup + Duration::from_secs_f64(timestamp);
+
+        let TimeUpdateStrategy::ManualInstant(time_update) = &mut *self.world.resource_mut::<TimeUpdateStrategy>() else {panic!()};
+        *time_update = instant;
+
+        self.run_stage(CoreStage::First);
+        self.run_stage(PhysicsStage::PrePhysics);
+        self.run_stage(PhysicsStage::RapierSyncBackend);
+        self.run_stage(PhysicsStage::RapierStepSimulation);
+        self.run_stage(PhysicsStage::RapierWriteback);
+        self.run_stage(PhysicsStage::RapierDetectDespawn);
+        self.run_stage(PhysicsStage::PostPhysics);
+
+        self.current_tick = Some(tick);
+
+        PhysicsEventBuffer::record(&mut self.world, tick, timestamp);
+        PhysicsHistory::capture(&mut self.world, tick);
+    }
+
+    /// Synchronously simulate `ticks` ticks, for physics apps driven without a main app.
+    ///
+    /// Removal trackers are cleared after each tick, as joining a task would otherwise do.
+    pub fn advance(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.step();
+            self.world.clear_trackers();
+        }
+
+        self.target_tick = self.current_tick().max(0) as usize;
+    }
+
+    /// Synchronously simulate to `tick`, rewinding first if it lies in the past.
+    pub fn advance_to(&mut self, tick: usize) -> Result<(), String> {
+        if (tick as isize) < self.current_tick() {
+            self.rewind(tick)?;
+        }
+
+        let ticks = tick as isize - self.current_tick();
+        self.advance(ticks.max(0) as usize);
+        Ok(())
+    }
+
+    /// The earliest tick [`PhysicsApp::rewind`] can reach,
+    /// or the current tick if there's no history to rewind through.
+    pub fn earliest_tick(&self) -> usize {
+        let current_tick = self.current_tick().max(0) as usize;
+
+        self.world
+            .get_resource::<PhysicsHistory>()
+            .and_then(PhysicsHistory::oldest)
+            .unwrap_or(current_tick)
+            .min(current_tick)
+    }
+
+    /// Restore the nearest snapshot at or before `tick`,
+    /// leaving the remaining ticks to be re-simulated by [`PhysicsApp::step`].
+    ///
+    /// Fails without changing anything if no snapshot is old enough.
+    pub fn rewind(&mut self, tick: usize) -> Result<(), String> {
+        let restored_tick = PhysicsHistory::restore(&mut self.world, tick)?;
+        self.current_tick = Some(restored_tick);
+
+        // Clear the mappings of the handles dropped by the restore, then re-create them.
+        // Trackers are cleared in between so the next despawn detection doesn't remove them again
+        self.run_stage(PhysicsStage::RapierDetectDespawn);
+        self.world.clear_trackers();
+        self.run_stage(PhysicsStage::RapierSyncBackend);
+
+        Ok(())
+    }
+}
+
+/// Shared state used by the main world to steer an in-flight [`PhysicsTask`].
+#[derive(Debug, Default, Clone)]
+pub struct PhysicsTaskControl {
+    target_tick: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl PhysicsTaskControl {
+    pub fn new(target_tick: usize) -> Self {
+        PhysicsTaskControl {
+            target_tick: Arc::new(AtomicUsize::new(target_tick)),
+            ..default()
+        }
+    }
+
+    pub fn target_tick(&self) -> usize {
+        self.target_tick.load(Ordering::Acquire)
+    }
+
+    /// Move the tick the task is simulating towards.
+    /// A target behind the task's current tick stops it early,
+    /// leaving the rewind to the next task.
+    pub fn retarget(&self, target_tick: usize) {
+        self.target_tick.store(target_tick, Ordering::Release);
+    }
+
+    /// Stop the task after its current tick, dropping its target
+    /// so the next task isn't dispatched until a new one is set.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct PhysicsTask<W = DefaultPhysicsWorld> {
+    task: Task<PhysicsApp<W>>,
+    pub control: PhysicsTaskControl,
+    pub clock: PhysicsClock,
+    pub timeline: Option<Entity>,
+}
+
+impl<W> Deref for PhysicsTask<W> {
+    type Target = Task<PhysicsApp<W>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.task
+    }
+}
+
+impl<W> DerefMut for PhysicsTask<W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.task
+    }
+}
+
+/// The simulation [`World`] of the application, stored as a resource.
+/// This resource is only available during [`PhysicsStage::Extract`] and not
+/// during command application of that stage.
+/// See [`Extract`] for more details.
+#[derive(Resource, Default)]
+pub struct MainWorld(World);
+
+impl Deref for MainWorld {
+    type Target = World;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for MainWorld {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A "scratch" world used to avoid allocating new worlds every frame when
+/// swapping out the [`MainWorld`] for [`PhysicsStage::Extract`].
+#[derive(Resource, Default)]
+pub struct ScratchMainWorld(World);
+
+pub fn fork_physics<T: WorldQuery + 'static, W: PhysicsWorld>(main_world: &mut World) {
+    let Some(mut physics_app) = main_world.remove_resource::<PhysicsApp<W>>() else {
+        return
+    };
+
+    let current_tick = physics_app.current_tick();
+    let target_tick = physics_app.target_tick as isize;
+    if target_tick == current_tick {
+        main_world.insert_resource(physics_app);
+        return;
+    }
+
+    physics_app.update_progress(&mut main_world.resource_mut::<PhysicsProgress<W>>());
+
+    debug!("Physics world ready, dispatching async");
+
+    if W::MAIN {
+        exclude_other_world_members(main_world);
+    }
+
+    // The physics world keeps its entities between tasks,
+    // so drop the copies of anything despawned or removed in the main world first
+    let mut removed = despawn_stale_entities::<W>(&mut physics_app.world, main_world);
+
+    removed |= detach_orphan_joints::<W>(main_world, &mut physics_app.world);
+
+    let removals = std::mem::take(&mut *main_world.resource_mut::<PhysicsRemovals<W>>());
+    if !removals.is_empty() {
+        removals.replay(&mut physics_app.world);
+        removed = true;
+    }
+
+    // Copy changed components from main world to physics world
+    extract::<T, W>(main_world, &mut physics_app);
+
+    // Hand over the translations queued since the last task
+    pend_translations::<W>(main_world, &mut physics_app.world);
+
+    // Free the handles of removed bodies and colliders before anything is simulated
+    if removed {
+        physics_app.run_stage(PhysicsStage::RapierDetectDespawn);
+    }
+
+    // Dispatch async task
+    let control = PhysicsTaskControl::new(physics_app.target_tick);
+    let clock = physics_app.clock();
+    let timeline = physics_app.timeline;
+    let task_control = control.clone();
+
+    let task_pool = AsyncComputeTaskPool::get();
+    let task = task_pool.spawn(async move {
+        let start = Instant::now();
+
+        let mut rewind_failed = false;
+        if target_tick < current_tick {
+            if let Err(e) = physics_app.rewind(target_tick as usize) {
+                error!("Failed to rewind physics to tick {target_tick}: {e}");
+                rewind_failed = true;
+            }
+        }
+
+        // Spread the handed over translations across the ticks left once any rewind has restored
+        let ticks = target_tick - physics_app.current_tick();
+        spread_pending_translations(&mut physics_app.world, ticks.max(0) as usize);
+
+        // Simulate as much as the budget allows, leaving the rest for subsequent tasks.
+        // The target is re-read every tick, as the main world may have moved it since dispatch
+        let mut ticks = 0;
+        while !task_control.is_cancelled()
+            && physics_app.current_tick() < task_control.target_tick() as isize
+            && physics_app.budget.allows(ticks, start.elapsed())
+        {
+            physics_app.step();
+            ticks += 1;
+        }
+
+        // Settle where the task stopped rather than retrying a failed rewind every frame
+        physics_app.target_tick = if task_control.is_cancelled() || rewind_failed {
+            physics_app.current_tick().max(0) as usize
+        } else {
+            task_control.target_tick()
+        };
+        physics_app.chunk_ticks = ticks;
+        physics_app.chunk_duration = start.elapsed();
+
+        // Predict from the freshly simulated state while still off the main thread
+        PhysicsPredictions::<W>::dispatch(&mut physics_app);
+
+        physics_app
+    });
+
+    main_world.insert_resource(PhysicsTask {
+        task,
+        control,
+        clock,
+        timeline,
+    });
+}
+
+pub fn join_physics<T: WorldQuery + 'static, W: PhysicsWorld>(main_world: &mut World) {
+    let Some(mut task) = main_world.remove_resource::<PhysicsTask<W>>() else {
+        return
+    };
+
+    debug!("Physics world not ready, polling future");
+    if let Some(mut async_app) =
+        futures_lite::future::block_on(futures_lite::future::poll_once(&mut *task))
+    {
+        debug!("Task finished, replacing world");
+
+        // Copy components from physics world to main world
+        writeback::<T, W>(main_world, &mut async_app);
+
+        // Snapshot the freshly joined state for queries, and hand over any finished prediction
+        PhysicsQuery::<W>::refresh(main_world, &async_app);
+        PhysicsPredictions::<W>::deliver(&mut async_app.world, main_world);
+
+        // Clear the removals produced by the task, so they aren't replayed next task
+        async_app.world.clear_trackers();
+
+        async_app.update_progress(&mut main_world.resource_mut::<PhysicsProgress<W>>());
+
+        main_world.insert_resource(async_app);
+    } else {
+        debug!("Task not finished, replacing");
+        main_world.insert_resource(task)
+    }
+}
+
+fn move_resource<T: Resource>(from: &mut World, to: &mut World) {
+    // Move resources
+    to.insert_resource(from.remove_resource::<T>().unwrap());
+}
+
+fn extract<T: WorldQuery + 'static, W: PhysicsWorld>(
+    main_world: &mut World,
+    physics_app: &mut PhysicsApp<W>,
+) {
+    // Move resources
+    if W::MAIN {
+        move_resource::<RapierContext>(main_world, &mut physics_app.world);
+        main_world.insert_resource(RapierContext::default());
+
+        move_resource::<RapierConfiguration>(main_world, &mut physics_app.world);
+        move_resource::<SimulationToRenderTime>(main_world, &mut physics_app.world);
+        move_resource::<Events<CollisionEvent>>(main_world, &mut physics_app.world);
+        move_resource::<Events<ContactForceEvent>>(main_world, &mut physics_app.world);
+        move_resource::<PhysicsHooksWithQueryResource<T>>(main_world, &mut physics_app.world);
+    }
+
+    // Pick up any tick rate changes before extracted timelines are snapped to it
+    PhysicsClock::sync(&mut physics_app.world);
+
+    PhysicsChanges::begin_extract(&mut physics_app.world);
+
+    // Run extract stage
+    let extract = physics_app
+        .schedule
+        .get_stage_mut::<SystemStage>(PhysicsStage::Extract)
+        .unwrap();
+
+    // temporarily add the app world to the render world as a resource
+    let scratch_world = main_world.remove_resource::<ScratchMainWorld>().unwrap();
+    let inserted_world = std::mem::replace(main_world, scratch_world.0);
+    let running_world = &mut physics_app.world;
+    running_world.insert_resource(MainWorld(inserted_world));
+
+    extract.run(running_world);
+    // move the app world back, as if nothing happened.
+    let inserted_world = running_world.remove_resource::<MainWorld>().unwrap();
+    let scratch_world = std::mem::replace(main_world, inserted_world.0);
+    main_world.insert_resource(ScratchMainWorld(scratch_world));
+
+    // Note: We apply buffers (read, Commands) after the `MainWorld` has been removed from the render app's world
+    // so that in future, pipelining will be able to do this too without any code relying on it.
+    // see <https://github.com/bevyengine/bevy/issues/5082>
+    extract.apply_buffers(running_world);
+
+    PhysicsChanges::end_extract(main_world, running_world);
+}
+
+fn writeback<T: WorldQuery + 'static, W: PhysicsWorld>(
+    main_world: &mut World,
+    async_app: &mut PhysicsApp<W>,
+) {
+    let writeback = async_app
+        .schedule
+        .get_stage_mut::<SystemStage>(PhysicsStage::Writeback)
+        .unwrap();
+
+    writeback.run(&mut async_app.world);
+
+    PhysicsChanges::writeback(main_world, &mut async_app.world, |main_world| {
+        writeback.apply_buffers(main_world)
+    });
+
+    PhysicsEventBuffer::deliver::<W>(&mut async_app.world, main_world);
+
+    // Move resources
+    if W::MAIN {
+        move_resource::<RapierContext>(&mut async_app.world, main_world);
+        move_resource::<RapierConfiguration>(&mut async_app.world, main_world);
+        move_resource::<SimulationToRenderTime>(&mut async_app.world, main_world);
+        move_resource::<Events<CollisionEvent>>(&mut async_app.world, main_world);
+        move_resource::<Events<ContactForceEvent>>(&mut async_app.world, main_world);
+        move_resource::<PhysicsHooksWithQueryResource<T>>(&mut async_app.world, main_world);
+    }
+}
+
+/// Mirrors timelines into the physics world, rounded down to whole ticks.
+pub fn extract_timeline(
+    mut commands: Commands,
+    clock: Res<PhysicsClock>,
+    query: Extract<Query<(Entity, &TimelineComponent)>>,
+) {
+    for (entity, timeline) in query.iter() {
+        let mut commands = commands.get_or_spawn(entity);
+        let mut timeline = *timeline;
+
+        timeline.timestamp = clock.snap(timeline.timestamp);
+        timeline.prev_timestamp = clock.snap(timeline.prev_timestamp);
+        commands.insert((timeline, Mirrored));
+    }
+}
+
+/// Mirrors the parent chain of every body and collider in `W` into its physics world,
+/// along with the transforms of any ancestors that aren't otherwise extracted.
+///
+/// Rapier's transform propagation then produces correct [`GlobalTransform`]s,
+/// and its writeback computes [`Transform`]s relative to the parent,
+/// which are valid in the main world as it shares the same hierarchy.
+pub fn extract_hierarchy<W: PhysicsWorld>(
+    mut commands: Commands,
+    mut visited: Local<HashSet<Entity>>,
+    query_bodies: Extract<Query<Entity, (RigidBodyOrCollider, W::Membership)>>,
+    query_parent: Extract<Query<&Parent>>,
+    query_transform: Extract<Query<(Option<&Transform>, Option<&GlobalTransform>)>>,
+    query_extracted_parent: Query<&Parent>,
+) {
+    visited.clear();
+
+    for entity in query_bodies.iter() {
+        // Detached in the main world since the last extraction
+        if !query_parent.contains(entity) && query_extracted_parent.contains(entity) {
+            commands.entity(entity).remove_parent();
+        }
+
+        let mut child = entity;
+        while let Ok(parent) = query_parent.get(child) {
+            let parent = parent.get();
+
+            let mut parent_commands = commands.get_or_spawn(parent);
+            parent_commands.insert(Mirrored);
+
+            if let Ok((transform, global_transform)) = query_transform.get(parent) {
+                if let Some(transform) = transform {
+                    parent_commands.insert(*transform);
+                }
+
+                if let Some(global_transform) = global_transform {
+                    parent_commands.insert(*global_transform);
+                }
+            }
+
+            // Reparenting touches both ends of the relationship, so only do so when it differs
+            let extracted_parent = query_extracted_parent.get(child).ok().map(Parent::get);
+            if extracted_parent != Some(parent) {
+                commands.get_or_spawn(child).set_parent(parent);
+            }
+
+            // Shared ancestors only need extracting once
+            if !visited.insert(parent) {
+                break;
+            }
+
+            child = parent;
+        }
+    }
+}
+
+pub mod extract_component {
+    use std::marker::PhantomData;
+
+    use bevy::{
+        ecs::query::ReadOnlyWorldQuery,
+        prelude::{
+            default, Commands, Component, Entity, Plugin, Query, Res, ResMut, SystemStage, With,
+        },
+    };
+
+    use super::{
+        changes::PhysicsChanges,
+        extract_param::Extract,
+        prediction::PhysicsPredictions,
+        removal::PhysicsRemovalPlugin,
+        world::{DefaultPhysicsWorld, Mirrored, PhysicsWorld},
+        MainWorld, PhysicsApp, PhysicsStage,
+    };
+
+    /// Copies `T` into the physics world `W` for entities matching `F`,
+    /// whenever it's missing there or has changed in the main world since the last extraction.
+    #[derive(Debug)]
+    pub struct ExtractComponentPlugin<T, F = (), W = DefaultPhysicsWorld> {
+        phantom: PhantomData<(T, F, W)>,
+    }
+
+    impl<T, F, W> Default for ExtractComponentPlugin<T, F, W> {
+        fn default() -> Self {
+            Self { phantom: default() }
+        }
+    }
+
+    impl<T, F, W> Plugin for ExtractComponentPlugin<T, F, W>
+    where
+        T: Clone + Component,
+        F: 'static + Send + Sync + ReadOnlyWorldQuery,
+        W: PhysicsWorld,
+    {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_startup_system(|mut physics_app: ResMut<PhysicsApp<W>>| {
+                physics_app
+                    .schedule
+                    .get_stage_mut::<SystemStage>(PhysicsStage::Extract)
+                    .unwrap()
+                    .add_system(extract_component::<T, (F, W::Membership)>);
+
+                // The physics world only holds members already
+                PhysicsPredictions::<W>::add_extract_system(
+                    &mut physics_app.world,
+                    extract_component::<T, F>,
+                );
+            });
+
+            app.add_plugin(PhysicsRemovalPlugin::<T, W>::default());
+        }
+    }
+
+    fn extract_component<T, F: ReadOnlyWorldQuery>(
+        mut commands: Commands,
+        mut changes: ResMut<PhysicsChanges>,
+        main_world: Res<MainWorld>,
+        query: Extract<Query<(Entity, &T), F>>,
+        query_extracted: Query<(), With<T>>,
+    ) where
+        T: Clone + Component,
+    {
+        let main_tick = main_world.read_change_tick();
+
+        for (entity, component) in query.iter() {
+            if query_extracted.contains(entity) {
+                let Some(ticks) = main_world.entity(entity).get_change_ticks::<T>() else {
+                    continue
+                };
+
+                if !changes.changed_in_main(ticks, main_tick) {
+                    continue;
+                }
+            }
+
+            let mut commands = commands.get_or_spawn(entity);
+            commands.insert((component.clone(), Mirrored));
+            changes.extracted.insert(entity);
+        }
+    }
+}
+
+pub mod writeback_component {
+    use std::marker::PhantomData;
+
+    use bevy::{
+        ecs::query::ReadOnlyWorldQuery,
+        prelude::{
+            default, Commands, Component, Entity, Plugin, Query, Res, ResMut, SystemStage, World,
+        },
+    };
+
+    use super::{
+        changes::PhysicsChanges,
+        world::{DefaultPhysicsWorld, PhysicsWorld},
+        PhysicsApp, PhysicsStage,
+    };
+
+    /// Copies `T` back into the main world for entities matching `F`,
+    /// whenever it has changed in the physics world `W` since the last extraction.
+    ///
+    /// Only main-world members of `W` are written to, so entities that have since left it
+    /// or been despawned aren't touched.
+    #[derive(Debug)]
+    pub struct WritebackComponentPlugin<T, F = (), W = DefaultPhysicsWorld> {
+        phantom: PhantomData<(T, F, W)>,
+    }
+
+    impl<T, F, W> Default for WritebackComponentPlugin<T, F, W> {
+        fn default() -> Self {
+            Self { phantom: default() }
+        }
+    }
+
+    impl<T, F, W> Plugin for WritebackComponentPlugin<T, F, W>
+    where
+        T: Clone + Component,
+        F: 'static + Send + Sync + ReadOnlyWorldQuery,
+        W: PhysicsWorld,
+    {
+        fn build(&self, app: &mut bevy::prelude::App) {
+            app.add_startup_system(|mut physics_app: ResMut<PhysicsApp<W>>| {
+                physics_app
+                    .schedule
+                    .get_stage_mut::<SystemStage>(PhysicsStage::Writeback)
+                    .unwrap()
+                    .add_system(writeback_component::<T, F, W::Membership>);
+            });
+        }
+    }
+
+    fn writeback_component<T, F, M>(
+        mut commands: Commands,
+        changes: Res<PhysicsChanges>,
+        world: &World,
+        query: Query<(Entity, &T), F>,
+    ) where
+        T: Clone + Component,
+        F: 'static + ReadOnlyWorldQuery,
+        M: 'static + ReadOnlyWorldQuery,
+    {
+        let physics_tick = world.read_change_tick();
+
+        let mut components = vec![];
+        for (entity, component) in query.iter() {
+            let Some(ticks) = world.entity(entity).get_change_ticks::<T>() else {
+                continue
+            };
+
+            if !changes.changed_in_physics(ticks, physics_tick) {
+                continue;
+            }
+
+            components.push((entity, component.clone()));
+        }
+
+        if components.is_empty() {
+            return;
+        }
+
+        // Applied to the main world, where membership is decided
+        commands.add(move |main_world: &mut World| {
+            let mut query_members = main_world.query_filtered::<(), M>();
+            for (entity, component) in components {
+                if query_members.get(main_world, entity).is_ok() {
+                    main_world.entity_mut(entity).insert(component);
+                }
+            }
+        });
+    }
+}
+
+pub mod extract_param {
+    use super::MainWorld;
+    use bevy::ecs::{
+        prelude::*,
+        system::{
+            ReadOnlySystemParamFetch, ResState, SystemMeta, SystemParam, SystemParamFetch,
+            SystemParamItem, SystemParamState, SystemState,
+        },
+    };
+    use std::ops::{Deref, DerefMut};
+
+    /// A helper for accessing [`MainWorld`] content using a system parameter.
+    ///
+    /// A [`SystemParam`] adapter which applies the contained `SystemParam` to the [`World`]
+    /// contained in [`MainWorld`]. This parameter only works for systems run
+    /// during [`RenderStage::Extract`].
+    ///
+    /// This requires that the contained [`SystemParam`] does not mutate the world, as it
+    /// uses a read-only reference to [`MainWorld`] internally.
+    ///
+    /// ## Context
+    ///
+    /// [`RenderStage::Extract`] is used to extract (move) data from the simulation world ([`MainWorld`]) to the
+    /// render world. The render world drives rendering each frame (generally to a [Window]).
+    /// This design is used to allow performing calculations related to rendering a prior frame at the same
+    /// time as the next frame is simulated, which increases throughput (FPS).
+    ///
+    /// [`Extract`] is used to get data from the main world during [`RenderStage::Extract`].
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    /// use bevy_render::Extract;
+    /// # #[derive(Component)]
+    /// # struct Cloud;
+    /// fn extract_clouds(mut commands: Commands, clouds: Extract<Query<Entity, With<Cloud>>>) {
+    ///     for cloud in &clouds {
+    ///         commands.get_or_spawn(cloud).insert(Cloud);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`RenderStage::Extract`]: crate::RenderStage::Extract
+    /// [Window]: bevy_window::Window
+    pub struct Extract<'w, 's, P: SystemParam + 'static>
+    where
+        P::Fetch: ReadOnlySystemParamFetch,
+    {
+        item: <P::Fetch as SystemParamFetch<'w, 's>>::Item,
+    }
+
+    impl<'w, 's, P: SystemParam> SystemParam for Extract<'w, 's, P>
+    where
+        P::Fetch: ReadOnlySystemParamFetch,
+    {
+        type Fetch = ExtractState<P>;
+    }
+
+    #[doc(hidden)]
+    pub struct ExtractState<P: SystemParam + 'static> {
+        state: SystemState<P>,
+        main_world_state: ResState<MainWorld>,
+    }
+
+    // SAFETY: only accesses MainWorld resource with read only system params using ResState,
+    // which is initialized in init()
+    unsafe impl<P: SystemParam + 'static> SystemParamState for ExtractState<P> {
+        fn init(world: &mut World, system_meta: &mut SystemMeta) -> Self {
+            let mut main_world = world.resource_mut::<MainWorld>();
+            Self {
+                state: SystemState::new(&mut main_world),
+                main_world_state: ResState::init(world, system_meta),
+            }
+        }
+    }
+
+    impl<'w, 's, P: SystemParam + 'static> SystemParamFetch<'w, 's> for ExtractState<P>
+    where
+        P::Fetch: ReadOnlySystemParamFetch,
+    {
+        type Item = Extract<'w, 's, P>;
+
+        unsafe fn get_param(
+            state: &'s mut Self,
+            system_meta: &SystemMeta,
+            world: &'w World,
+            change_tick: u32,
+        ) -> Self::Item {
+            let main_world = ResState::<MainWorld>::get_param(
+                &mut state.main_world_state,
+                system_meta,
+                world,
+                change_tick,
+            );
+            let item = state.state.get(main_world.into_inner());
+            Extract { item }
+        }
+    }
+
+    impl<'w, 's, P: SystemParam> Deref for Extract<'w, 's, P>
+    where
+        P::Fetch: ReadOnlySystemParamFetch,
+    {
+        type Target = <P::Fetch as SystemParamFetch<'w, 's>>::Item;
+
+        #[inline]
+        fn deref(&self) -> &Self::Target {
+            &self.item
+        }
+    }
+
+    impl<'w, 's, P: SystemParam> DerefMut for Extract<'w, 's, P>
+    where
+        P::Fetch: ReadOnlySystemParamFetch,
+    {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.item
+        }
+    }
+
+    impl<'a, 'w, 's, P: SystemParam> IntoIterator for &'a Extract<'w, 's, P>
+    where
+        P::Fetch: ReadOnlySystemParamFetch,
+        &'a SystemParamItem<'w, 's, P>: IntoIterator,
+    {
+        type Item = <&'a SystemParamItem<'w, 's, P> as IntoIterator>::Item;
+        type IntoIter = <&'a SystemParamItem<'w, 's, P> as IntoIterator>::IntoIter;
+
+        fn into_iter(self) -> Self::IntoIter {
+            (&self.item).into_iter()
+        }
+    }
+}
+
+/// The timeline `timeline` refers to, or the only timeline if `None`.
+fn find_timeline<'a>(
+    query: &'a Query<&TimelineComponent>,
+    timeline: Option<Entity>,
+) -> Option<&'a TimelineComponent> {
+    match timeline {
+        Some(timeline) => query.get(timeline).ok(),
+        None => query.get_single().ok(),
+    }
+}
+
+/// The timeline the physics world `W` follows, whether it's idle or running a task.
+fn world_timeline<'a, W: PhysicsWorld>(
+    physics_app: Option<&PhysicsApp<W>>,
+    physics_task: Option<&PhysicsTask<W>>,
+    query: &'a Query<&TimelineComponent>,
+) -> Option<&'a TimelineComponent> {
+    let timeline = physics_app
+        .map(|physics_app| physics_app.timeline)
+        .or_else(|| physics_task.map(|physics_task| physics_task.timeline))?;
+
+    find_timeline(query, timeline)
+}
+
+fn dispatch_physics<W: PhysicsWorld>(
+    physics_app: Option<ResMut<PhysicsApp<W>>>,
+    physics_task: Option<Res<PhysicsTask<W>>>,
+    query: Query<&TimelineComponent>,
+) {
+    let timestamp = |timeline: Option<Entity>| {
+        find_timeline(&query, timeline).map(|timeline| timeline.0.timestamp)
+    };
+
+    // Steer any in-flight task towards the new target,
+    // or stop it if the timeline it follows is gone
+    if let Some(physics_task) = physics_task {
+        match timestamp(physics_task.timeline) {
+            Some(timestamp) => {
+                let target_tick = physics_task.clock.tick_at(timestamp);
+                physics_task.control.retarget(target_tick);
+            }
+            None => physics_task.control.cancel(),
+        }
+    }
+
+    let Some(mut physics_app) = physics_app else {
+        return;
+    };
+
+    // Keep the current target until the timeline is back
+    let Some(timestamp) = timestamp(physics_app.timeline) else {
+        return;
+    };
+
+    // Ticks from before the history's oldest snapshot can't be rewound to
+    let target_tick = physics_app.clock().tick_at(timestamp);
+    physics_app.target_tick = target_tick.max(physics_app.earliest_tick());
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{default, Entity, GlobalTransform, Transform, TransformBundle, World};
+    use bevy_rapier3d::prelude::{
+        Collider, ImpulseJoint, RapierConfiguration, RapierImpulseJointHandle, RigidBody,
+        SphericalJointBuilder, TimestepMode, Vect,
+    };
+
+    use super::{
+        world::{
+            despawn_stale_entities, detach_orphan_joints, DefaultPhysicsWorld, Mirrored,
+            OrphanJoint,
+        },
+        PhysicsApp, PhysicsAppBuilder,
+    };
+
+    /// A headless physics app with a snapshot every tick.
+    fn headless_app() -> PhysicsApp {
+        headless_app_with_retention(600)
+    }
+
+    fn headless_app_with_retention(retention: usize) -> PhysicsApp {
+        PhysicsAppBuilder::<()>::default()
+            .with_history(retention, 1)
+            .map(|app| {
+                app.insert_resource(RapierConfiguration {
+                    gravity: Vect::NEG_Y * 9.81,
+                    timestep_mode: TimestepMode::Fixed {
+                        dt: 1.0 / 60.0,
+                        substeps: 1,
+                    },
+                    ..default()
+                });
+            })
+            .build_headless()
+    }
+
+    fn at_height(y: f32) -> TransformBundle {
+        let transform = Transform::from_xyz(0.0, y, 0.0);
+        TransformBundle {
+            local: transform,
+            global: GlobalTransform::from(transform),
+        }
+    }
+
+    /// A ball dropped from 10m.
+    fn falling_ball() -> (PhysicsApp, Entity) {
+        let mut physics_app = headless_app();
+
+        let entity = physics_app
+            .world
+            .spawn((RigidBody::Dynamic, Collider::ball(0.5), at_height(10.0)))
+            .id();
+
+        (physics_app, entity)
+    }
+
+    fn transform(physics_app: &PhysicsApp, entity: Entity) -> Transform {
+        *physics_app.world.get::<Transform>(entity).unwrap()
+    }
+
+    #[test]
+    fn advance_moves_falling_body() {
+        let (mut physics_app, entity) = falling_ball();
+
+        physics_app.advance(30);
+
+        assert_eq!(physics_app.current_tick(), 29);
+        assert!(transform(&physics_app, entity).translation.y < 10.0);
+    }
+
+    #[test]
+    fn advance_to_earlier_tick_restores_state() {
+        let (mut physics_app, entity) = falling_ball();
+
+        physics_app.advance_to(20).unwrap();
+        let earlier = transform(&physics_app, entity);
+
+        physics_app.advance_to(40).unwrap();
+        let later = transform(&physics_app, entity);
+        assert_ne!(later, earlier);
+
+        physics_app.advance_to(20).unwrap();
+        assert_eq!(physics_app.current_tick(), 20);
+        assert_eq!(transform(&physics_app, entity), earlier);
+
+        // Only matches if Rapier's own state was restored, not just the snapshotted transforms
+        physics_app.advance_to(40).unwrap();
+        assert_eq!(transform(&physics_app, entity), later);
+    }
+
+    #[test]
+    fn advance_to_first_tick_outside_retention() {
+        let mut physics_app = headless_app_with_retention(10);
+
+        let entity = physics_app
+            .world
+            .spawn((RigidBody::Dynamic, Collider::ball(0.5), at_height(10.0)))
+            .id();
+
+        physics_app.advance_to(0).unwrap();
+        let first = transform(&physics_app, entity);
+
+        physics_app.advance_to(60).unwrap();
+        assert_eq!(physics_app.earliest_tick(), 0);
+
+        physics_app.advance_to(0).unwrap();
+        assert_eq!(physics_app.current_tick(), 0);
+        assert_eq!(transform(&physics_app, entity), first);
+    }
+
+    #[test]
+    fn joint_holds_child_to_parent() {
+        let mut physics_app = headless_app();
+
+        let parent = physics_app
+            .world
+            .spawn((RigidBody::Fixed, at_height(10.0)))
+            .id();
+
+        let joint = SphericalJointBuilder::new().local_anchor2(Vect::Y);
+        let child = physics_app
+            .world
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::ball(0.25),
+                ImpulseJoint::new(parent, joint),
+                at_height(9.0),
+            ))
+            .id();
+
+        physics_app.advance(60);
+
+        assert!(physics_app
+            .world
+            .get::<RapierImpulseJointHandle>(child)
+            .is_some());
+
+        let distance = transform(&physics_app, child)
+            .translation
+            .distance(transform(&physics_app, parent).translation);
+        assert!((distance - 1.0).abs() < 0.05, "{distance}");
+    }
+
+    #[test]
+    fn joint_without_member_parent_is_skipped() {
+        let mut main_world = World::new();
+        let mut physics_world = World::new();
+
+        let parent = main_world.spawn(at_height(10.0)).id();
+        let child = main_world
+            .spawn((
+                RigidBody::Dynamic,
+                ImpulseJoint::new(parent, SphericalJointBuilder::new()),
+            ))
+            .id();
+
+        assert!(detach_orphan_joints::<DefaultPhysicsWorld>(
+            &mut main_world,
+            &mut physics_world
+        ));
+        assert!(main_world.get::<OrphanJoint>(child).is_some());
+
+        // Only warned about once
+        assert!(!detach_orphan_joints::<DefaultPhysicsWorld>(
+            &mut main_world,
+            &mut physics_world
+        ));
+
+        main_world.entity_mut(parent).insert(RigidBody::Fixed);
+        detach_orphan_joints::<DefaultPhysicsWorld>(&mut main_world, &mut physics_world);
+        assert!(main_world.get::<OrphanJoint>(child).is_none());
+    }
+
+    #[test]
+    fn stale_entities_spare_physics_spawned_ones() {
+        let mut main_world = World::new();
+        let mut physics_world = World::new();
+
+        let kept = main_world.spawn(RigidBody::Dynamic).id();
+        physics_world.get_or_spawn(kept).unwrap().insert(Mirrored);
+
+        let despawned = main_world.spawn_empty().id();
+        physics_world.get_or_spawn(despawned).unwrap().insert(Mirrored);
+        main_world.despawn(despawned);
+
+        let spawned = physics_world.spawn(RigidBody::Dynamic).id();
+
+        assert!(despawn_stale_entities::<DefaultPhysicsWorld>(
+            &mut physics_world,
+            &mut main_world
+        ));
+        assert!(physics_world.get_entity(kept).is_some());
+        assert!(physics_world.get_entity(despawned).is_none());
+        assert!(physics_world.get_entity(spawned).is_some());
+    }
+}
@@ -0,0 +1,334 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::query::WorldQuery,
+    prelude::{
+        App, Component, Entity, Events, IntoSystemDescriptor, Plugin, Resource, SystemStage,
+        Transform, With, World,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+    utils::HashMap,
+};
+use bevy_rapier3d::{
+    pipeline::ContactForceEvent,
+    prelude::{
+        CollisionEvent, RapierColliderHandle, RapierConfiguration, RapierContext,
+        RapierImpulseJointHandle, RapierMultibodyJointHandle, RapierRigidBodyHandle, RigidBody,
+    },
+    utils::iso_to_transform,
+};
+
+use crate::physics_component;
+
+use super::{
+    component::PhysicsComponentAppExt,
+    events::PhysicsEventBuffer,
+    history::PhysicsHistory,
+    world::{DefaultPhysicsWorld, PhysicsWorld},
+    PhysicsApp, PhysicsAppBuilder, PhysicsStage,
+};
+
+/// Requests a prediction of where this entity's body will be over the next `ticks` ticks.
+///
+/// Predictions start from the state each physics task ends on,
+/// as long as the previous one has finished, and are delivered when a task is joined.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct PredictTrajectory {
+    pub ticks: usize,
+}
+
+physics_component!(PredictTrajectory: extract(With<RigidBody>));
+
+/// Predicted global transforms of an entity's body, one per tick after `start_tick`.
+#[derive(Debug, Default, Clone, Component)]
+pub struct PredictedTrajectory {
+    pub start_tick: usize,
+    pub dt: f64,
+    pub transforms: Vec<Transform>,
+}
+
+impl PredictedTrajectory {
+    /// Iterate the predicted transforms alongside the timeline timestamp of their tick.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, &Transform)> {
+        self.transforms
+            .iter()
+            .enumerate()
+            .map(|(i, transform)| ((self.start_tick + i + 1) as f64 * self.dt, transform))
+    }
+}
+
+/// Per-entity transform tracks produced by simulating a copy of the physics world forward.
+#[derive(Debug, Default, Clone)]
+pub struct PhysicsPrediction {
+    pub start_tick: usize,
+    pub dt: f64,
+    pub tracks: HashMap<Entity, Vec<Transform>>,
+}
+
+/// The copy of the physics world `W` that predictions are simulated in.
+///
+/// It's forked from `W`'s physics world rather than the main world,
+/// so every entity it's extracted from is already a member.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Predicted<W> {
+    phantom: PhantomData<W>,
+}
+
+impl<W: PhysicsWorld> PhysicsWorld for Predicted<W> {
+    type Membership = ();
+
+    const MAIN: bool = false;
+}
+
+/// Build the app predictions for `W` are simulated in, configured by the same `maps`
+/// as `W`'s own physics app, so they run the same stages and systems.
+///
+/// Rapier resources aren't shared with it, so physics hooks only apply to predictions
+/// when inserted through [`PhysicsAppBuilder::map`].
+pub(super) fn build_prediction_app<T, W>(maps: &[Box<dyn Fn(&mut App)>]) -> PhysicsApp<Predicted<W>>
+where
+    T: 'static + Send + Sync + WorldQuery,
+    W: PhysicsWorld,
+{
+    let mut builder = PhysicsAppBuilder::<T, Predicted<W>>::default();
+    for f in maps {
+        f(&mut builder.app);
+    }
+
+    // Predictions only ever step forward from a fresh fork
+    builder.app.world.remove_resource::<PhysicsHistory>();
+
+    let mut prediction_app = PhysicsApp::from(builder.app);
+    prediction_app.run_startup();
+    prediction_app
+}
+
+/// Physics-world state of the predictions for entities requesting a [`PredictTrajectory`] in `W`.
+///
+/// The prediction app is forked from the physics world at the end of a task,
+/// then stepped through [`PhysicsApp::step`] on the async compute pool,
+/// so hooks, character controllers and user systems are all accounted for.
+/// Solver state such as contact warm-starting isn't carried over by the fork.
+#[derive(Debug, Resource)]
+pub struct PhysicsPredictions<W = DefaultPhysicsWorld> {
+    /// The prediction app, while no prediction is running.
+    app: Option<PhysicsApp<Predicted<W>>>,
+    task: Option<Task<(PhysicsApp<Predicted<W>>, PhysicsPrediction)>>,
+    /// The last finished prediction, until it's delivered.
+    prediction: Option<PhysicsPrediction>,
+}
+
+impl<W: PhysicsWorld> PhysicsPredictions<W> {
+    pub fn new(app: PhysicsApp<Predicted<W>>) -> Self {
+        PhysicsPredictions {
+            app: Some(app),
+            task: None,
+            prediction: None,
+        }
+    }
+
+    /// Add an extract system to the prediction app, which reads from `W`'s physics world.
+    pub(super) fn add_extract_system<Params>(
+        physics_world: &mut World,
+        system: impl IntoSystemDescriptor<Params>,
+    ) {
+        let Some(mut predictions) = physics_world.get_resource_mut::<PhysicsPredictions<W>>()
+        else {
+            return
+        };
+
+        let Some(prediction_app) = predictions.app.as_mut() else {
+            return
+        };
+
+        prediction_app
+            .schedule
+            .get_stage_mut::<SystemStage>(PhysicsStage::Extract)
+            .unwrap()
+            .add_system(system);
+    }
+
+    /// Take the prediction app back from a finished prediction.
+    fn collect(&mut self) {
+        let Some(task) = self.task.as_mut() else {
+            return
+        };
+
+        let Some((app, prediction)) =
+            futures_lite::future::block_on(futures_lite::future::poll_once(task))
+        else {
+            return
+        };
+
+        self.task = None;
+        self.app = Some(app);
+        self.prediction = Some(prediction);
+    }
+
+    /// Start a prediction from the state `physics_app` has just simulated to,
+    /// unless one is still running. Called at the end of each task, off the main thread.
+    pub(super) fn dispatch(physics_app: &mut PhysicsApp<W>) {
+        let Some(mut predictions) = physics_app.world.remove_resource::<PhysicsPredictions<W>>()
+        else {
+            return
+        };
+
+        predictions.collect();
+
+        let requests: Vec<(Entity, usize)> = physics_app
+            .world
+            .query::<(Entity, &PredictTrajectory)>()
+            .iter(&physics_app.world)
+            .map(|(entity, predict)| (entity, predict.ticks))
+            .collect();
+
+        let ticks = requests.iter().map(|(_, ticks)| *ticks).max();
+
+        // Still running the previous prediction otherwise
+        let prediction_app = ticks.and_then(|_| predictions.app.take());
+
+        if let (Some(ticks), Some(mut prediction_app)) = (ticks, prediction_app) {
+            fork(physics_app, &mut prediction_app);
+
+            let entities: Vec<Entity> = requests.into_iter().map(|(entity, _)| entity).collect();
+
+            let task_pool = AsyncComputeTaskPool::get();
+            predictions.task = Some(task_pool.spawn(async move {
+                let prediction = predict(&mut prediction_app, ticks, &entities);
+                (prediction_app, prediction)
+            }));
+        }
+
+        physics_app.world.insert_resource(predictions);
+    }
+
+    /// Insert the last finished prediction into the main world as [`PredictedTrajectory`] components,
+    /// for entities that still request one.
+    pub fn deliver(physics_world: &mut World, main_world: &mut World) {
+        let Some(mut predictions) = physics_world.get_resource_mut::<PhysicsPredictions<W>>()
+        else {
+            return
+        };
+
+        predictions.collect();
+
+        let Some(prediction) = predictions.prediction.take() else {
+            return
+        };
+
+        let mut query = main_world.query_filtered::<&PredictTrajectory, W::Membership>();
+
+        let mut trajectories = vec![];
+        for (entity, mut transforms) in prediction.tracks {
+            // No longer requested
+            let Ok(predict) = query.get(main_world, entity) else {
+                continue
+            };
+
+            transforms.truncate(predict.ticks);
+
+            trajectories.push((
+                entity,
+                PredictedTrajectory {
+                    start_tick: prediction.start_tick,
+                    dt: prediction.dt,
+                    transforms,
+                },
+            ));
+        }
+
+        for (entity, trajectory) in trajectories {
+            main_world.entity_mut(entity).insert(trajectory);
+        }
+    }
+}
+
+/// Replace the prediction app's world with a copy of `physics_app`'s,
+/// extracted through the prediction app's own extract stage.
+fn fork<W: PhysicsWorld>(
+    physics_app: &mut PhysicsApp<W>,
+    prediction_app: &mut PhysicsApp<Predicted<W>>,
+) {
+    let world = &mut prediction_app.world;
+    world.clear_entities();
+
+    // Rapier's state is rebuilt from the extracted components
+    world.insert_resource(RapierContext::default());
+    world.insert_resource(*physics_app.world.resource::<RapierConfiguration>());
+    world.insert_resource(Events::<CollisionEvent>::default());
+    world.insert_resource(Events::<ContactForceEvent>::default());
+    world.insert_resource(PhysicsEventBuffer::default());
+
+    super::extract::<(), Predicted<W>>(&mut physics_app.world, prediction_app);
+
+    // Handles refer to the physics world's Rapier sets, so have Rapier create new ones
+    let world = &mut prediction_app.world;
+    let entities: Vec<Entity> = world.iter_entities().collect();
+    for entity in entities {
+        world.entity_mut(entity).remove_intersection::<(
+            RapierRigidBodyHandle,
+            RapierColliderHandle,
+            RapierImpulseJointHandle,
+            RapierMultibodyJointHandle,
+        )>();
+    }
+
+    world.clear_trackers();
+
+    prediction_app.current_tick = physics_app.current_tick;
+}
+
+/// Step `prediction_app` for `ticks` ticks, recording the global transforms of `entities` after each.
+fn predict<W: PhysicsWorld>(
+    prediction_app: &mut PhysicsApp<Predicted<W>>,
+    ticks: usize,
+    entities: &[Entity],
+) -> PhysicsPrediction {
+    let mut prediction = PhysicsPrediction {
+        start_tick: prediction_app.current_tick().max(0) as usize,
+        dt: prediction_app.clock().dt,
+        tracks: entities
+            .iter()
+            .map(|entity| (*entity, Vec::with_capacity(ticks)))
+            .collect(),
+    };
+
+    for _ in 0..ticks {
+        prediction_app.advance(1);
+
+        let context = prediction_app.world.resource::<RapierContext>();
+        for (entity, track) in prediction.tracks.iter_mut() {
+            let Some(body) = context
+                .entity2body()
+                .get(entity)
+                .and_then(|handle| context.bodies.get(*handle))
+            else {
+                continue
+            };
+
+            track.push(iso_to_transform(body.position(), context.physics_scale()));
+        }
+    }
+
+    prediction
+}
+
+/// Runs [`PredictTrajectory`] requests against the physics world `W`,
+/// delivering the results as [`PredictedTrajectory`] components.
+pub struct PhysicsPredictionPlugin<W = DefaultPhysicsWorld> {
+    phantom: PhantomData<W>,
+}
+
+impl<W> Default for PhysicsPredictionPlugin<W> {
+    fn default() -> Self {
+        PhysicsPredictionPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<W: PhysicsWorld> Plugin for PhysicsPredictionPlugin<W> {
+    fn build(&self, app: &mut App) {
+        app.register_physics_component_in::<PredictTrajectory, W>();
+    }
+}
@@ -0,0 +1,111 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::{Res, ResMut, Resource},
+};
+
+use super::world::{DefaultPhysicsWorld, PhysicsWorld};
+
+/// How much simulation a single physics task may perform before
+/// its results are written back and a new task is dispatched.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PhysicsBudget {
+    /// Simulate all the way to the target tick in one task.
+    Unbounded,
+    /// Simulate at most this many ticks per task.
+    Ticks(usize),
+    /// Stop simulating once a task has run for this long.
+    Duration(Duration),
+}
+
+impl Default for PhysicsBudget {
+    fn default() -> Self {
+        PhysicsBudget::Duration(Duration::from_millis(8))
+    }
+}
+
+impl PhysicsBudget {
+    /// Whether a task that has simulated `ticks` ticks over `elapsed` may simulate another.
+    /// Always allows the first tick so progress is guaranteed.
+    pub fn allows(&self, ticks: usize, elapsed: Duration) -> bool {
+        if ticks == 0 {
+            return true;
+        }
+
+        match self {
+            PhysicsBudget::Unbounded => true,
+            PhysicsBudget::Ticks(max) => ticks < *max,
+            PhysicsBudget::Duration(max) => elapsed < *max,
+        }
+    }
+}
+
+/// Catch-up state of the physics world `W`, updated as tasks are dispatched and joined.
+#[derive(Debug, Default, Copy, Clone, Resource)]
+pub struct PhysicsProgress<W: PhysicsWorld = DefaultPhysicsWorld> {
+    pub current_tick: isize,
+    pub target_tick: usize,
+    pub ticks_remaining: usize,
+    /// Ticks simulated by the last completed task.
+    pub chunk_ticks: usize,
+    /// Wall-clock time taken by the last completed task.
+    pub chunk_duration: Duration,
+    /// Entities that had components copied into the physics world for the last task.
+    pub extracted_entities: usize,
+    pub world: PhantomData<W>,
+}
+
+impl<W: PhysicsWorld> PhysicsProgress<W> {
+    pub fn ticks_per_second(&self) -> f64 {
+        let seconds = self.chunk_duration.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+
+        self.chunk_ticks as f64 / seconds
+    }
+}
+
+pub struct PhysicsDiagnostics;
+
+impl PhysicsDiagnostics {
+    pub const TICKS_REMAINING: DiagnosticId =
+        DiagnosticId::from_u128(190235412370985203946257381624091761733);
+    pub const TICKS_PER_SECOND: DiagnosticId =
+        DiagnosticId::from_u128(27801735968216409345520976431287359021);
+    pub const EXTRACTED_ENTITIES: DiagnosticId =
+        DiagnosticId::from_u128(118640519263450827310648725309175224817);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::TICKS_REMAINING,
+            "physics_ticks_remaining",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::TICKS_PER_SECOND,
+            "physics_ticks_per_second",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::EXTRACTED_ENTITIES,
+            "physics_extracted_entities",
+            20,
+        ));
+    }
+
+    pub fn diagnostic_system<W: PhysicsWorld>(
+        mut diagnostics: ResMut<Diagnostics>,
+        progress: Res<PhysicsProgress<W>>,
+    ) {
+        diagnostics.add_measurement(Self::TICKS_REMAINING, || progress.ticks_remaining as f64);
+        diagnostics.add_measurement(Self::EXTRACTED_ENTITIES, || {
+            progress.extracted_entities as f64
+        });
+
+        if progress.chunk_ticks > 0 {
+            diagnostics.add_measurement(Self::TICKS_PER_SECOND, || progress.ticks_per_second());
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::{marker::PhantomData, ops::Deref};
+
+use bevy::prelude::{Resource, World};
+use bevy_rapier3d::prelude::RapierContext;
+
+use super::{
+    world::{DefaultPhysicsWorld, PhysicsWorld},
+    PhysicsApp,
+};
+
+/// Read-only copy of the physics world `W`'s [`RapierContext`] as of the last completed task,
+/// for ray casts, shape casts and intersection tests from main-world systems.
+///
+/// The main world's own `RapierContext` is an empty placeholder while a task is in flight,
+/// whereas this stays valid throughout.
+///
+/// Copies are only made on request, through [`PhysicsQuery::request_scene`]
+/// and [`PhysicsQuery::request_contacts`], and are left empty otherwise.
+/// They don't include Rapier's entity maps.
+/// Entity lookups go through collider user data, so results map back to entities as usual,
+/// but filters excluding a specific rigid body entity have no effect.
+#[derive(Resource)]
+pub struct PhysicsQuery<W = DefaultPhysicsWorld> {
+    context: RapierContext,
+    tick: isize,
+    /// Whether the bodies, colliders and query pipeline were requested since the last refresh.
+    scene_requested: bool,
+    /// Whether the narrow phase was requested since the last refresh.
+    contacts_requested: bool,
+    phantom: PhantomData<W>,
+}
+
+impl<W> Default for PhysicsQuery<W> {
+    fn default() -> Self {
+        PhysicsQuery {
+            context: RapierContext::default(),
+            tick: -1,
+            scene_requested: false,
+            contacts_requested: false,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<W> Deref for PhysicsQuery<W> {
+    type Target = RapierContext;
+
+    fn deref(&self) -> &Self::Target {
+        &self.context
+    }
+}
+
+impl<W: PhysicsWorld> PhysicsQuery<W> {
+    /// The tick the queryable state was captured at, or -1 before the first task completes.
+    pub fn tick(&self) -> isize {
+        self.tick
+    }
+
+    /// Have the next joined task's bodies, colliders and query pipeline copied,
+    /// for ray casts, shape casts and intersection tests.
+    ///
+    /// Requests only last until that join,
+    /// so systems reading the copy should request it every frame.
+    pub fn request_scene(&mut self) {
+        self.scene_requested = true;
+    }
+
+    /// Have the next joined task's narrow phase copied along with its scene,
+    /// for reading contacts and intersection pairs.
+    pub fn request_contacts(&mut self) {
+        self.scene_requested = true;
+        self.contacts_requested = true;
+    }
+
+    /// Copy the requested parts of the state `physics_app` was just joined with.
+    pub fn refresh(main_world: &mut World, physics_app: &PhysicsApp<W>) {
+        let Some(mut query) = main_world.get_resource_mut::<PhysicsQuery<W>>() else {
+            return
+        };
+
+        query.tick = physics_app.current_tick();
+
+        let scene_requested = std::mem::take(&mut query.scene_requested);
+        let contacts_requested = std::mem::take(&mut query.contacts_requested);
+
+        // Joined worlds hand their context back to the main world, others keep it
+        let context_world = if W::MAIN {
+            &*main_world
+        } else {
+            &physics_app.world
+        };
+
+        // Only what queries read is copied, the solver's state is left behind
+        let context = context_world.resource::<RapierContext>();
+        let scene = scene_requested.then(|| {
+            (
+                context.bodies.clone(),
+                context.colliders.clone(),
+                context.query_pipeline.clone(),
+            )
+        });
+        let narrow_phase = contacts_requested.then(|| context.narrow_phase.clone());
+
+        let mut query = main_world.resource_mut::<PhysicsQuery<W>>();
+        let (bodies, colliders, query_pipeline) = scene.unwrap_or_default();
+        query.context.bodies = bodies;
+        query.context.colliders = colliders;
+        query.context.query_pipeline = query_pipeline;
+        query.context.narrow_phase = narrow_phase.unwrap_or_default();
+    }
+}
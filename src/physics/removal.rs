@@ -0,0 +1,199 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use bevy::{
+    prelude::{
+        Commands, Component, CoreStage, Entity, Plugin, Query, RemovedComponents, ResMut, Resource,
+        With, World,
+    },
+    utils::HashMap,
+};
+use bevy_rapier3d::prelude::{
+    Collider, ImpulseJoint, MultibodyJoint, RapierColliderHandle, RapierImpulseJointHandle,
+    RapierMultibodyJointHandle, RapierRigidBodyHandle, RigidBody,
+};
+
+use super::{
+    world::{DefaultPhysicsWorld, PhysicsWorld},
+    PhysicsApp,
+};
+
+type ReplayFn = Box<dyn Fn(&mut World, &[Entity]) + Send + Sync>;
+
+/// Entities that lost an extracted component in the main world since the last extraction,
+/// keyed by the component whose removal should be replayed in the physics world `W`.
+#[derive(Debug, Resource)]
+pub struct PhysicsRemovals<W: PhysicsWorld = DefaultPhysicsWorld> {
+    removed: HashMap<TypeId, Vec<Entity>>,
+    phantom: PhantomData<W>,
+}
+
+impl<W: PhysicsWorld> Default for PhysicsRemovals<W> {
+    fn default() -> Self {
+        PhysicsRemovals {
+            removed: HashMap::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<W: PhysicsWorld> PhysicsRemovals<W> {
+    pub fn push<T: Component>(&mut self, entity: Entity) {
+        self.removed
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push(entity);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.values().all(Vec::is_empty)
+    }
+
+    /// Replay the recorded removals in the physics world, so that Rapier's removal detection
+    /// frees the corresponding handles on its next run.
+    pub fn replay(self, physics_world: &mut World) {
+        let Some(replays) = physics_world.remove_resource::<RemovalReplays>() else {
+            return
+        };
+
+        for (type_id, entities) in self.removed.iter() {
+            if let Some(replay) = replays.0.get(type_id) {
+                replay(physics_world, entities);
+            }
+        }
+
+        physics_world.insert_resource(replays);
+    }
+}
+
+/// Physics-world registry of replay functions for each component tracked by [`PhysicsRemovals`].
+#[derive(Default, Resource)]
+struct RemovalReplays(HashMap<TypeId, ReplayFn>);
+
+/// Tracks removals of `T` in the main world, replaying them on the physics world `W`'s
+/// copy of the entity, which otherwise keeps its components between tasks.
+///
+/// Added for every extracted component by
+/// [`ExtractComponentPlugin`](super::extract_component::ExtractComponentPlugin).
+pub struct PhysicsRemovalPlugin<T, W = DefaultPhysicsWorld> {
+    phantom: PhantomData<(T, W)>,
+}
+
+impl<T, W> Default for PhysicsRemovalPlugin<T, W> {
+    fn default() -> Self {
+        PhysicsRemovalPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, W> Plugin for PhysicsRemovalPlugin<T, W>
+where
+    T: Component,
+    W: PhysicsWorld,
+{
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<PhysicsRemovals<W>>();
+
+        // Removals are only visible until the end of the frame, so track them once
+        // after everything else has run, leaving them to be replayed by the next task
+        app.add_system_to_stage(CoreStage::Last, track_removals::<T, W>);
+
+        app.add_startup_system(|mut physics_app: ResMut<PhysicsApp<W>>| {
+            physics_app
+                .world
+                .get_resource_or_insert_with(RemovalReplays::default)
+                .0
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Box::new(replay::<T>));
+        });
+    }
+}
+
+fn track_removals<T: Component, W: PhysicsWorld>(
+    mut removals: ResMut<PhysicsRemovals<W>>,
+    removed: RemovedComponents<T>,
+    query: Query<(), With<T>>,
+) {
+    for entity in removed.iter() {
+        // Re-added since
+        if query.contains(entity) {
+            continue;
+        }
+
+        removals.push::<T>(entity);
+    }
+}
+
+fn replay<T: Component>(world: &mut World, entities: &[Entity]) {
+    for entity in entities.iter().copied() {
+        // Despawned along with its main-world counterpart
+        let Some(mut entity) = world.get_entity_mut(entity) else {
+            continue
+        };
+
+        entity.remove::<T>();
+    }
+}
+
+/// Removes the main world's handles of entities that are no longer bodies, colliders or joints,
+/// which Rapier frees in the physics world but has no way to clean up here.
+///
+/// Only needed by the physics world owning the main world's handles.
+pub struct RapierRemovalPlugin<W = DefaultPhysicsWorld> {
+    phantom: PhantomData<W>,
+}
+
+impl<W> Default for RapierRemovalPlugin<W> {
+    fn default() -> Self {
+        RapierRemovalPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<W: PhysicsWorld> Plugin for RapierRemovalPlugin<W> {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        if !W::MAIN {
+            return;
+        }
+
+        app.add_system_to_stage(
+            CoreStage::Last,
+            remove_stale::<RigidBody, RapierRigidBodyHandle, W>,
+        )
+        .add_system_to_stage(
+            CoreStage::Last,
+            remove_stale::<Collider, RapierColliderHandle, W>,
+        )
+        .add_system_to_stage(
+            CoreStage::Last,
+            remove_stale::<ImpulseJoint, RapierImpulseJointHandle, W>,
+        )
+        .add_system_to_stage(
+            CoreStage::Last,
+            remove_stale::<MultibodyJoint, RapierMultibodyJointHandle, W>,
+        );
+    }
+}
+
+/// Removes `R` from entities that lost `T`.
+///
+/// The removal is recorded for the physics world directly, as it's only applied
+/// after [`track_removals`] has run for the frame.
+fn remove_stale<T: Component, R: Component, W: PhysicsWorld>(
+    mut commands: Commands,
+    mut removals: ResMut<PhysicsRemovals<W>>,
+    removed: RemovedComponents<T>,
+    query: Query<(), With<T>>,
+    query_stale: Query<(), With<R>>,
+) {
+    for entity in removed.iter() {
+        // Re-added since, or already without a handle
+        if query.contains(entity) || !query_stale.contains(entity) {
+            continue;
+        }
+
+        commands.entity(entity).remove::<R>();
+        removals.push::<R>(entity);
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use bevy::{
+    ecs::query::ReadOnlyWorldQuery,
+    prelude::{default, warn, Component, Entity, Or, Resource, With, Without, World},
+    utils::HashSet,
+};
+use bevy_rapier3d::prelude::{
+    Collider, ImpulseJoint, MultibodyJoint, RapierImpulseJointHandle, RapierMultibodyJointHandle,
+    RigidBody,
+};
+
+/// Identifies one of several independent physics worlds,
+/// each with its own [`PhysicsApp`](super::PhysicsApp), tick rate and timeline binding.
+pub trait PhysicsWorld: 'static + Send + Sync + Debug + Default + Copy + Clone {
+    /// Filter selecting the main-world entities that are extracted into this world.
+    type Membership: 'static + Send + Sync + ReadOnlyWorldQuery;
+
+    /// Whether this world simulates the main world's Rapier resources,
+    /// as opposed to owning a separate set inside its physics world.
+    const MAIN: bool;
+}
+
+/// The physics world driving gameplay, which owns the main world's `RapierContext`.
+///
+/// Every entity is a member, except those belonging to another physics world.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DefaultPhysicsWorld;
+
+impl PhysicsWorld for DefaultPhysicsWorld {
+    type Membership = Without<NotInDefaultPhysicsWorld>;
+
+    const MAIN: bool = true;
+}
+
+/// Marks a member of another physics world, excluding it from the [`DefaultPhysicsWorld`].
+///
+/// Maintained by [`exclude_other_world_members`], so shouldn't be inserted by hand.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct NotInDefaultPhysicsWorld;
+
+/// Main-world registry of the members of every physics world besides the default one.
+#[derive(Default, Resource)]
+pub struct OtherPhysicsWorlds(Vec<fn(&mut World) -> Vec<Entity>>);
+
+impl OtherPhysicsWorlds {
+    pub fn register<W: PhysicsWorld>(&mut self) {
+        self.0.push(members::<W>);
+    }
+}
+
+fn members<W: PhysicsWorld>(main_world: &mut World) -> Vec<Entity> {
+    main_world
+        .query_filtered::<Entity, W::Membership>()
+        .iter(main_world)
+        .collect()
+}
+
+/// Mark the members of other physics worlds with [`NotInDefaultPhysicsWorld`],
+/// and unmark entities that have since left them,
+/// so no entity is simulated and written back by two worlds at once.
+pub fn exclude_other_world_members(main_world: &mut World) {
+    let Some(worlds) = main_world.get_resource::<OtherPhysicsWorlds>() else {
+        return
+    };
+
+    let worlds = worlds.0.clone();
+    let members: HashSet<Entity> = worlds
+        .iter()
+        .flat_map(|world_members| world_members(main_world))
+        .collect();
+
+    let excluded: HashSet<Entity> = main_world
+        .query_filtered::<Entity, With<NotInDefaultPhysicsWorld>>()
+        .iter(main_world)
+        .collect();
+
+    for entity in excluded.difference(&members) {
+        main_world
+            .entity_mut(*entity)
+            .remove::<NotInDefaultPhysicsWorld>();
+    }
+
+    for entity in members.difference(&excluded) {
+        main_world
+            .entity_mut(*entity)
+            .insert(NotInDefaultPhysicsWorld);
+    }
+}
+
+/// Marks an entity whose joint's parent isn't a rigid body of the same physics world,
+/// so the joint isn't extracted.
+///
+/// Maintained by [`detach_orphan_joints`], so shouldn't be inserted by hand.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct OrphanJoint;
+
+type Jointed = Or<(With<ImpulseJoint>, With<MultibodyJoint>)>;
+
+/// Mark the joints of `W`'s members whose parent isn't a member rigid body with [`OrphanJoint`],
+/// warning about each once, and unmark those whose parent has since joined.
+///
+/// Newly orphaned joints and their handles are removed from both worlds,
+/// so Rapier frees them and re-creates them once they're extracted again.
+/// Returns whether any were.
+pub fn detach_orphan_joints<W: PhysicsWorld>(
+    main_world: &mut World,
+    physics_world: &mut World,
+) -> bool {
+    let mut query_parents = main_world.query_filtered::<(), (With<RigidBody>, W::Membership)>();
+    let mut query_joints = main_world.query_filtered::<(
+        Entity,
+        Option<&ImpulseJoint>,
+        Option<&MultibodyJoint>,
+        Option<&OrphanJoint>,
+    ), (Jointed, W::Membership)>();
+
+    let mut orphaned = vec![];
+    let mut adopted = vec![];
+    for (entity, impulse_joint, multibody_joint, orphan) in query_joints.iter(main_world) {
+        let missing_parent = impulse_joint
+            .map(|joint| joint.parent)
+            .into_iter()
+            .chain(multibody_joint.map(|joint| joint.parent))
+            .find(|parent| query_parents.get(main_world, *parent).is_err());
+
+        match (missing_parent, orphan.is_some()) {
+            (Some(parent), false) => orphaned.push((entity, parent)),
+            (None, true) => adopted.push(entity),
+            _ => (),
+        }
+    }
+
+    for (entity, parent) in orphaned.iter() {
+        warn!("Skipping the joint of {entity:?}, its parent {parent:?} isn't in its physics world");
+
+        main_world
+            .entity_mut(*entity)
+            .insert(OrphanJoint)
+            .remove_intersection::<(RapierImpulseJointHandle, RapierMultibodyJointHandle)>();
+
+        if let Some(mut entity) = physics_world.get_entity_mut(*entity) {
+            entity.remove_intersection::<(
+                ImpulseJoint,
+                MultibodyJoint,
+                RapierImpulseJointHandle,
+                RapierMultibodyJointHandle,
+            )>();
+        }
+    }
+
+    for entity in adopted {
+        main_world.entity_mut(entity).remove::<OrphanJoint>();
+    }
+
+    !orphaned.is_empty()
+}
+
+/// Marks an entity as a member of the physics world `W`,
+/// for worlds using `With<InPhysicsWorld<W>>` as their [`PhysicsWorld::Membership`].
+#[derive(Debug, Copy, Clone, Component)]
+pub struct InPhysicsWorld<W: PhysicsWorld> {
+    phantom: PhantomData<W>,
+}
+
+impl<W: PhysicsWorld> Default for InPhysicsWorld<W> {
+    fn default() -> Self {
+        InPhysicsWorld { phantom: default() }
+    }
+}
+
+/// Membership filter for worlds whose entities opt in via [`InPhysicsWorld`].
+pub type InPhysicsWorldFilter<W> = With<InPhysicsWorld<W>>;
+
+/// Marks a physics-world entity as the copy of a main-world one,
+/// as opposed to one spawned by physics-side systems.
+///
+/// Inserted by extraction, so shouldn't be inserted by hand.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct Mirrored;
+
+/// Despawn [`Mirrored`] entities whose main-world counterpart has since been despawned,
+/// along with bodies and colliders that are no longer members of `W`,
+/// so Rapier's removal detection frees their handles. Returns whether any were.
+///
+/// Entities spawned by physics-side systems have no counterpart, so are left alone.
+pub fn despawn_stale_entities<W: PhysicsWorld>(
+    physics_world: &mut World,
+    main_world: &mut World,
+) -> bool {
+    let mut query_members = main_world.query_filtered::<(), W::Membership>();
+    let mut query_mirrored = physics_world.query_filtered::<Entity, With<Mirrored>>();
+    let mut query_bodies =
+        physics_world.query_filtered::<(), Or<(With<RigidBody>, With<Collider>)>>();
+
+    let main_world = &*main_world;
+
+    let mut is_stale = |entity: Entity| {
+        main_world.get_entity(entity).is_none()
+            || (query_bodies.get(physics_world, entity).is_ok()
+                && query_members.get(main_world, entity).is_err())
+    };
+
+    let stale: Vec<Entity> = query_mirrored
+        .iter(physics_world)
+        .filter(|entity| is_stale(*entity))
+        .collect();
+
+    for entity in stale.iter() {
+        physics_world.despawn(*entity);
+    }
+
+    !stale.is_empty()
+}
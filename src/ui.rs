@@ -5,16 +5,15 @@ use std::{
 
 use bevy::{
     diagnostic::{DiagnosticId, Diagnostics},
-    prelude::{default, Camera, Local, Plugin, Query, Res, ResMut, UVec2},
+    prelude::{default, AssetServer, Camera, Local, Plugin, Query, Res, ResMut, UVec2},
     render::camera::Viewport,
 };
 use bevy_egui::{
     egui::{self, plot::Line, Frame, Ui},
     EguiContext,
 };
-use bevy_rapier3d::prelude::RapierContext;
 
-use crate::timeline::TimelineComponent;
+use crate::{image_loader::ImageLoader, physics::query::PhysicsQuery, timeline::TimelineComponent};
 
 pub struct UiPlugin;
 
@@ -23,8 +22,9 @@ impl Plugin for UiPlugin {
         app.add_plugin(bevy_egui::EguiPlugin);
 
         app.add_system(timeline_panel)
-            .add_system(diagnostic_widget);
-            //.add_system(intersection_widget);
+            .add_system(diagnostic_widget)
+            .add_system(image_failure_widget)
+            .add_system(intersection_widget);
 
         // NOTE: Breaks bloom, fix coming in bevy 0.10
         //app.add_system(camera_viewport.after(diagnostic_panel));
@@ -60,13 +60,41 @@ fn timeline_panel(mut ctx: ResMut<EguiContext>, mut query: Query<&mut TimelineCo
     });
 }
 
-fn intersection_widget(mut ctx: ResMut<EguiContext>, rapier: Res<RapierContext>) {
-    egui::Window::new("Intersections").show(ctx.ctx_mut(), |ui| {
-        for (lhs, rhs, intersection) in rapier.intersection_pairs() {
+fn intersection_widget(mut ctx: ResMut<EguiContext>, mut physics_query: ResMut<PhysicsQuery>) {
+    let response = egui::Window::new("Intersections").show(ctx.ctx_mut(), |ui| {
+        for (lhs, rhs, intersection) in physics_query.intersection_pairs() {
             let text = format!("{lhs:?} / {rhs:?}: {intersection:}");
             ui.label(text);
         }
     });
+
+    // Intersections are only copied for queries while they're listed
+    if response.and_then(|response| response.inner).is_some() {
+        physics_query.request_contacts();
+    }
+}
+
+fn image_failure_widget(
+    mut ctx: ResMut<EguiContext>,
+    image_loader: Option<Res<ImageLoader>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(image_loader) = image_loader else {
+        return
+    };
+
+    if image_loader.failures().next().is_none() {
+        return;
+    }
+
+    egui::Window::new("Image Load Failures").show(ctx.ctx_mut(), |ui| {
+        for (handle, error) in image_loader.failures() {
+            match asset_server.get_handle_path(handle) {
+                Some(path) => ui.label(format!("{:?}: {error}", path.path())),
+                None => ui.label(format!("{handle:?}: {error}")),
+            };
+        }
+    });
 }
 
 fn diagnostic_widget(